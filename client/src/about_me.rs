@@ -1,70 +1,126 @@
+use crate::blog_displayer::view_markdown;
+use crate::errors::view_invalid_response;
+use crate::request_loader::{Displayer, RequestLoader};
+use crate::spinner::spinner;
+use anyhow::Error;
+use shared::authors::{Author, Authors, ProfileLink};
+use shared::markdown::{self, Extensions, RenderOptions};
+use yew::format::Json;
 use yew::prelude::*;
 
-pub struct AboutMe;
+// Both take the site's extensions to render the bios with
+pub type AboutMeComponent = RequestLoader<AboutMe, Json<Result<Authors, Error>>, Extensions>;
+
+pub type AuthorProfileComponent =
+    RequestLoader<AuthorProfile, Json<Result<Author, Error>>, Extensions>;
 
-impl Component for AboutMe {
-    type Message = ();
-    type Properties = ();
+const MAIL_ICON: &str = "icon-mail-squared";
 
-    fn create(_: Self::Properties, _: ComponentLink<Self>) -> Self {
-        AboutMe
+// The profiles the icon font has an icon for, shown by the icon alone
+fn social_icon(url: &str) -> Option<&'static str> {
+    let host = url.split("://").nth(1)?.split('/').next()?;
+    match host.trim_start_matches("www.") {
+        "twitter.com" => Some("icon-twitter"),
+        "linkedin.com" => Some("icon-linkedin"),
+        "github.com" => Some("icon-github"),
+        _ => None,
     }
+}
 
-    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
-        false
+fn view_link(link: &ProfileLink) -> Html {
+    let icon = if link.url.starts_with("mailto:") {
+        html! { <i class=MAIL_ICON style="display: inline;"/> }
+    } else {
+        html! {}
+    };
+    html! {
+        <a href=link.url.clone() style="display: block;">
+            {icon}
+            <p style="display: inline;">
+                {&link.label}
+            </p>
+        </a>
     }
+}
 
-    fn update(&mut self, _: Self::Message) -> ShouldRender {
-        false
+fn view_social_link(link: &ProfileLink, icon: &str) -> Html {
+    html! {
+        <a href=link.url.clone() title=link.label.clone() style="display: inline;">
+            <i class=icon/>
+        </a>
     }
+}
+
+pub fn view_author(author: &Author, extensions: Extensions) -> Html {
+    let bio = markdown::render(
+        &author.bio,
+        &RenderOptions {
+            anchor_prefix: None,
+            extensions,
+            playground: false,
+        },
+    );
+    let (social, others): (Vec<_>, Vec<_>) = author
+        .links
+        .iter()
+        .partition(|link| social_icon(&link.url).is_some());
 
-    fn view(&self) -> Html {
-        html! {
-            <div style="padding:1em; text-align: center; font-size: 1.1em;">
-                <b style="font-weight: 1000;">{"About Me"}</b>
-                <div>
-                    <img src="./imgs/kitten-white.png#profile" />
-                </div>
-                <p>{"Name: Gabriel Steinberg"}</p>
-                <p>{"Location: Argentina, CABA"}</p>
-                <p>{"I bang my head against the keyboard and sometimes a software happens 🌟"}</p>
-                <p>{"I like that software better when it's written in Rust or C++ 🦀"}</p>
-                <p>{"In this blog I will talk about anything that interest me, from system-programming to functional programming or anything else"}</p>
-                <p>{"My pronouns are he/him and I'm from Argentina(thus, the broken english)"}</p>
-                <p>{"This blog is pretty much a WIP so you'll see it changing pretty often."}</p>
-                <b style="font-weight: 1000;">{"Contact"}</b>
-                <p>{"Don't doubt to contact me for anything you want, I don't mind. Whether you want to talk to me about your project, you have a doubt or anything else I love talking to fellow programmers(as long as you're not rude)"}</p>
-                <p>{"Also you can contact me for any possible project! (paid or unpaid)"}</p>
+    html! {
+        <div id=author.id.clone() style="padding:1em; text-align: center; font-size: 1.1em;">
+            <b style="font-weight: 1000;">{&author.name}</b>
+            {
+                match &author.avatar {
+                    Some(avatar) => html! { <div><img src=avatar.clone() /></div> },
+                    None => html! {},
+                }
+            }
+            {
+                match &author.location {
+                    Some(location) => html! { <p>{format!("Location: {}", location)}</p> },
+                    None => html! {},
+                }
+            }
+            <div class="markdown-body">{view_markdown(&bio.html)}</div>
+            { for others.into_iter().map(view_link) }
+            <p style="display: block;">
+                {
+                    for social.into_iter().filter_map(|link| {
+                        social_icon(&link.url).map(|icon| view_social_link(link, icon))
+                    })
+                }
+            </p>
+        </div>
+    }
+}
 
-                <p>{"Use any of the following contacts, DMs to any of the platforms will be read(or mails)"}</p>
-                <a href="mailto:taping-memory@protonmail.com" style="display: block;">
-                    <i class="icon-mail-squared" style="display: inline;"/>
-                    <p style="display: inline;">
-                        {"Regarding this blog"}
-                    </p>
-                </a>
-                <a href="mailto:gabrielalejandro7@gmail.com" style="display: block;">
-                    <i class="icon-mail-squared" style="display: inline;" />
-                    <p style="display: inline;">
-                        {"Regarding work/projects or personal"}
-                    </p>
-                </a>
-                <p style="display: block;">
-                    <a href="https://twitter.com/MemoryTaping" style="display: inline;">
-                        <i class="icon-twitter" />
-                    </a>
-                    <a href="https://www.linkedin.com/in/gabriel-alejandro-steinberg-40186a155" style="display: inline;">
-                        <i class="icon-linkedin"/>
-                    </a>
-                    <a href="https://github.com/conectado/" style="display: inline;">
-                        <i class="icon-github"/>
-                    </a>
+// Every author of the site
+pub struct AboutMe;
+
+impl Displayer<Json<Result<Authors, Error>>, Extensions> for AboutMe {
+    fn display(value: &Option<Json<Result<Authors, Error>>>, extensions: Extensions) -> Html {
+        match value {
+            Some(Json(Ok(Authors { authors }))) if authors.is_empty() => html! {
+                <p class="text-element-white" style="padding:1em; text-align: center;">
+                    {"Nothing to see here yet"}
                 </p>
-                <p/>
-                <a href="https://www.behance.net/akooros">
-                    {"Profile art by akooros"}
-                </a>
-            </div>
+            },
+            Some(Json(Ok(Authors { authors }))) => html! {
+                <>{ for authors.iter().map(|author| view_author(author, extensions)) }</>
+            },
+            Some(Json(Err(_))) => view_invalid_response(),
+            None => spinner(),
+        }
+    }
+}
+
+pub struct AuthorProfile;
+
+impl Displayer<Json<Result<Author, Error>>, Extensions> for AuthorProfile {
+    fn display(value: &Option<Json<Result<Author, Error>>>, extensions: Extensions) -> Html {
+        match value {
+            Some(Json(Ok(author))) => view_author(author, extensions),
+            Some(Json(Err(_))) => view_invalid_response(),
+            None => spinner(),
         }
     }
 }
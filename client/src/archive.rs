@@ -0,0 +1,84 @@
+use crate::errors::view_invalid_response;
+use crate::request_loader::Displayer;
+use crate::request_loader::RequestLoader;
+use crate::routes::AppRoute;
+use crate::spinner::spinner;
+use anyhow::Error;
+use shared::archive::{Archive, ArchiveMonth, ArchiveYear};
+use yew::format::Json;
+use yew::prelude::*;
+use yew_router::components::RouterAnchor;
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+pub type ArchiveComponent = RequestLoader<ArchiveDisplayer, Json<Result<Archive, Error>>, ()>;
+
+fn view_month(month: &ArchiveMonth) -> Html {
+    let name = (month.month as usize)
+        .checked_sub(1)
+        .and_then(|i| MONTHS.get(i))
+        .copied()
+        .unwrap_or_default();
+    html! {
+        <li>
+            {format!("{} ({})", name, month.articles.len())}
+            <ul>
+                {
+                    for month.articles.iter().map(|article| html! {
+                        <li>
+                            <RouterAnchor<AppRoute> route={AppRoute::ViewPost(article.name.clone())}>
+                                {&article.title}
+                            </RouterAnchor<AppRoute>>
+                        </li>
+                    })
+                }
+            </ul>
+        </li>
+    }
+}
+
+// Only the latest year starts expanded
+fn view_year(year: &ArchiveYear, open: bool) -> Html {
+    html! {
+        <details open=open>
+            <summary>{format!("{} ({})", year.year, year.count)}</summary>
+            <ul>
+                {for year.months.iter().map(view_month)}
+            </ul>
+        </details>
+    }
+}
+
+pub struct ArchiveDisplayer;
+
+impl Displayer<Json<Result<Archive, Error>>, ()> for ArchiveDisplayer {
+    fn display(value: &Option<Json<Result<Archive, Error>>>, _: ()) -> Html {
+        match value {
+            Some(json) => match &json.0 {
+                Ok(archive) => html! {
+                    <div class="bg-element-dark text-element-white archive">
+                        <b style="font-weight: 1000;">{"Archive"}</b>
+                        {
+                            for archive.years.iter().enumerate().map(|(i, year)| view_year(year, i == 0))
+                        }
+                    </div>
+                },
+                _ => view_invalid_response(),
+            },
+            None => spinner(),
+        }
+    }
+}
@@ -1,12 +1,23 @@
+use crate::code_blocks::{mount_copy_buttons, Unmount};
+use crate::errors::{view_invalid_response, FetchError};
+use crate::playground::mount_playgrounds;
 use crate::request_loader::Displayer;
 use crate::request_loader::RequestLoader;
+use crate::routes::AppRoute;
 use crate::spinner::spinner;
 use anyhow::Error;
-use pulldown_cmark as pc;
+use shared::article_list::{ArticleLink, ArticleMetadata};
+use shared::authors::AuthorLink;
+use shared::constants;
+use shared::markdown::{self, Extensions, RenderOptions};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use yew::format::Json;
 use yew::html;
+use yew::prelude::{Callback, Component, ComponentLink, MouseEvent, Properties, ShouldRender};
 use yew::virtual_dom::VNode;
 use yew::{web_sys, Html};
+use yew_router::components::RouterAnchor;
 
 #[wasm_bindgen]
 extern "C" {
@@ -16,49 +27,186 @@ extern "C" {
     pub fn highlightBlock(block: JsValue);
 }
 
-pub type BlogDisplayerComponent = RequestLoader<BlogDisplayer, Result<String, Error>, ()>;
+pub type BlogDisplayerComponent =
+    RequestLoader<BlogDisplayer, Result<String, Error>, ArticleView>;
 
-fn create_markdown_container() -> web_sys::Element {
+pub type ArticleHeaderComponent =
+    RequestLoader<ArticleHeader, Json<Result<ArticleMetadata, Error>>, ()>;
+
+pub type ArticleLinksComponent =
+    RequestLoader<ArticleLinks, Json<Result<ArticleMetadata, Error>>, ()>;
+
+pub fn reading_stats(metadata: &ArticleMetadata) -> String {
+    format!(
+        "{} min read · {} words",
+        metadata.reading_time, metadata.word_count
+    )
+}
+
+// Previews leave everything but the extensions as default, the article page sets the article's
+// name and, when following a link to a heading, the heading to scroll to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArticleView {
+    pub name: Option<String>,
+    pub section: Option<String>,
+    // The site's, from the server
+    pub extensions: Extensions,
+}
+
+fn create_container(class_name: &str) -> web_sys::Element {
     let window = web_sys::window().expect("Can't find window");
     let document = window.document().expect("Can't find document");
     let div = document.create_element("div").expect("Couldn't create div");
-    div.set_class_name("markdown-body");
+    div.set_class_name(class_name);
     div
 }
 
-fn view_markdown(value: &str) -> Html {
-    let parser = pc::Parser::new(value);
-    let mut html_output = String::new();
-    pc::html::push_html(&mut html_output, parser);
+// The article isn't in the document until this render is done, so the scroll is deferred
+pub fn scroll_to(id: String) {
+    let scroll = Closure::once_into_js(move || {
+        let element = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id(&id));
+        if let Some(element) = element {
+            element.scroll_into_view();
+        }
+    });
+    if let Some(window) = web_sys::window() {
+        let _ = window.set_timeout_with_callback(scroll.unchecked_ref());
+    }
+}
 
-    let div = create_markdown_container();
+fn view_toc_sidebar(toc_html: &str) -> Html {
+    let div = create_container("toc-sidebar");
+    div.set_inner_html(toc_html);
+    VNode::VRef(web_sys::Node::from(div))
+}
 
-    div.set_inner_html(&html_output);
+fn render_markdown(html_output: &str) -> (web_sys::Element, Vec<Unmount>) {
+    let div = create_container("markdown-body");
+
+    div.set_inner_html(html_output);
 
     let code_blocks = div.query_selector_all("pre code").unwrap();
     for i in 0..code_blocks.length() {
         hljs::highlightBlock(JsValue::from(code_blocks.get(i).unwrap()));
     }
+    let mut unmounts = mount_copy_buttons(&div);
+    unmounts.extend(mount_playgrounds(&div));
+
+    (div, unmounts)
+}
+
+// Rendered markdown, with the components mounted on its code blocks living as long as it does
+pub struct MarkdownBody {
+    html: String,
+    div: web_sys::Element,
+    unmounts: Vec<Unmount>,
+}
 
-    let node = web_sys::Node::from(div);
-    VNode::VRef(node)
+#[derive(Properties, Clone, PartialEq)]
+pub struct MarkdownBodyProps {
+    pub html: String,
+}
+
+impl MarkdownBody {
+    fn unmount(&mut self) {
+        for unmount in self.unmounts.drain(..) {
+            unmount.emit(());
+        }
+    }
+}
+
+impl Component for MarkdownBody {
+    type Properties = MarkdownBodyProps;
+    type Message = ();
+
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        let (div, unmounts) = render_markdown(&props.html);
+        MarkdownBody {
+            html: props.html,
+            div,
+            unmounts,
+        }
+    }
+
+    fn update(&mut self, _: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.html == props.html {
+            return false;
+        }
+        self.unmount();
+        let (div, unmounts) = render_markdown(&props.html);
+        self.html = props.html;
+        self.div = div;
+        self.unmounts = unmounts;
+        true
+    }
+
+    fn view(&self) -> Html {
+        VNode::VRef(web_sys::Node::from(self.div.clone()))
+    }
+
+    fn destroy(&mut self) {
+        self.unmount();
+    }
+}
+
+pub fn view_markdown(html_output: &str) -> Html {
+    html! {<MarkdownBody html=html_output.to_string() />}
 }
 
 pub struct BlogDisplayer;
 
-impl Displayer<Result<String, Error>, ()> for BlogDisplayer {
-    fn display(text: &Option<Result<String, Error>>, _: ()) -> VNode {
+fn view_article(value: &str, view: &ArticleView) -> Html {
+    let anchor_prefix = view
+        .name
+        .as_ref()
+        .map(|name| format!("#{}/{}/", constants::ARTICLES_PATH, name));
+    let rendered = markdown::render(
+        value,
+        &RenderOptions {
+            anchor_prefix: anchor_prefix.as_deref(),
+            extensions: view.extensions,
+            // Previews are too short to run anything
+            playground: view.name.is_some(),
+        },
+    );
+
+    if let Some(section) = &view.section {
+        scroll_to(section.clone());
+    }
+
+    // Articles without an inline table of contents get one on the side
+    let sidebar = match &anchor_prefix {
+        Some(prefix) if !rendered.inline_toc && !rendered.toc.is_empty() => {
+            view_toc_sidebar(&markdown::toc_html(&rendered.toc, prefix))
+        }
+        _ => html! {},
+    };
+
+    html! {
+        <>
+            {sidebar}
+            <div style="word-break: break-word" class="text-element-white markdown-body">
+                {view_markdown(&rendered.html)}
+            </div>
+        </>
+    }
+}
+
+impl Displayer<Result<String, Error>, ArticleView> for BlogDisplayer {
+    fn display(text: &Option<Result<String, Error>>, view: ArticleView) -> VNode {
         html! {
             <div style="word-break: break-word" class="bg-element-dark">
                 {
                     match &text {
                         Some(result) => match result {
-                            Ok(value) => html! {
-                                <div style="word-break: break-word" class="text-element-white markdown-body">
-                                    {view_markdown(value)}
-                                </div>
-                            },
-                            _ => html! { <p>{"error"}</p> },
+                            Ok(value) => view_article(value, &view),
+                            _ => view_invalid_response(),
                         },
                         None => spinner(),
                     }
@@ -67,3 +215,111 @@ impl Displayer<Result<String, Error>, ()> for BlogDisplayer {
         }
     }
 }
+
+pub struct ArticleHeader;
+
+// `By <author>, <author> · ` before the reading stats, linking to their profiles
+fn view_authors(authors: &[AuthorLink]) -> Html {
+    if authors.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <>
+            {"By "}
+            {
+                for authors.iter().enumerate().map(|(i, author)| html! {
+                    <>
+                        {if i > 0 { ", " } else { "" }}
+                        <RouterAnchor<AppRoute> route={AppRoute::Author(author.id.clone())}>
+                            {&author.name}
+                        </RouterAnchor<AppRoute>>
+                    </>
+                })
+            }
+            {" · "}
+        </>
+    }
+}
+
+impl Displayer<Json<Result<ArticleMetadata, Error>>, ()> for ArticleHeader {
+    fn display(value: &Option<Json<Result<ArticleMetadata, Error>>>, _: ()) -> VNode {
+        match value {
+            Some(json) => match &json.0 {
+                Ok(metadata) => html! {
+                    <p class="reading-stats bg-element-dark">
+                        {view_authors(&metadata.authors)}
+                        {reading_stats(metadata)}
+                    </p>
+                },
+                // The article itself reports the error, the header can just be left out
+                _ => html! {},
+            },
+            None => html! {},
+        }
+    }
+
+    fn display_error(_: &FetchError, _: Callback<MouseEvent>) -> Html {
+        html! {}
+    }
+}
+
+pub struct ArticleLinks;
+
+fn view_article_links(title: &str, links: &[ArticleLink]) -> Html {
+    if links.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <div class="article-links">
+            <h5>{title}</h5>
+            <ul>
+                {
+                    for links.iter().map(|link| html! {
+                        <li>
+                            <RouterAnchor<AppRoute> route={AppRoute::ViewPost(link.name.clone())}>
+                                {&link.title}
+                            </RouterAnchor<AppRoute>>
+                        </li>
+                    })
+                }
+            </ul>
+        </div>
+    }
+}
+
+fn view_neighbour(label: &str, link: &Option<ArticleLink>, class: &str) -> Html {
+    match link {
+        Some(link) => html! {
+            <RouterAnchor<AppRoute> route={AppRoute::ViewPost(link.name.clone())} classes=class>
+                <small>{label}</small>
+                <div>{&link.title}</div>
+            </RouterAnchor<AppRoute>>
+        },
+        None => html! {},
+    }
+}
+
+impl Displayer<Json<Result<ArticleMetadata, Error>>, ()> for ArticleLinks {
+    fn display(value: &Option<Json<Result<ArticleMetadata, Error>>>, _: ()) -> VNode {
+        match value {
+            Some(Json(Ok(metadata))) => html! {
+                <div class="bg-element-dark">
+                    <div class="article-navigation">
+                        {view_neighbour("« Previous", &metadata.previous, "article-previous")}
+                        {view_neighbour("Next »", &metadata.next, "article-next")}
+                    </div>
+                    {view_article_links("Referenced by", &metadata.backlinks)}
+                    {view_article_links("Related posts", &metadata.related)}
+                </div>
+            },
+            // Like the header, nothing to show if the metadata couldn't be loaded
+            _ => html! {},
+        }
+    }
+
+    fn display_error(_: &FetchError, _: Callback<MouseEvent>) -> Html {
+        html! {}
+    }
+}
@@ -1,90 +1,426 @@
-use crate::blog_displayer::BlogDisplayerComponent;
+use crate::blog_displayer::{reading_stats, ArticleView, BlogDisplayerComponent};
+use crate::date::format_date;
+use crate::errors::view_invalid_response;
+use crate::infinite_scroll::InfiniteList;
 use crate::request_loader::Displayer;
 use crate::request_loader::RequestLoader;
 use crate::routes::AppRoute;
 use crate::spinner::spinner;
 use anyhow::Error;
-use shared::article_list::Articles;
-use std::cmp::min;
+use shared::article_list::{ArticleMetadata, Articles, SortOrder};
+use shared::constants;
+use shared::markdown::Extensions;
+use shared::pagination_indexes::{page_count, page_indexes, page_links, PageLink};
 use yew::format::Json;
 use yew::prelude::*;
+use yew::services::storage::{Area, StorageService};
+use yew_router::agent::{RouteAgentDispatcher, RouteRequest};
 use yew_router::components::RouterAnchor;
+use yew_router::route::Route;
 
-const PAGE_SIZE: usize = 5;
+// Numbered pages shown on each side of the current one
+const PAGE_WINDOW: usize = 2;
+// Whether the reader prefers infinite scroll to pages, remembered in local storage
+const INFINITE_SCROLL_KEY: &str = "infinite_scroll";
 
 pub type BlogPreviewListDisplayerComponent =
-    RequestLoader<BlogPreviewListDisplayer, Json<Result<Articles, Error>>, usize>;
+    RequestLoader<BlogPreviewListDisplayer, Json<Result<Articles, Error>>, ListView>;
+
+// The list is fetched already sorted, the sort is needed to keep it when changing pages
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListView {
+    pub page_number: usize,
+    pub sort: SortOrder,
+    pub infinite_scroll: bool,
+}
+
+pub fn page_route(page_number: usize, sort: SortOrder) -> AppRoute {
+    if sort == SortOrder::default() {
+        AppRoute::Page(page_number)
+    } else {
+        AppRoute::SortedPage(page_number, sort.to_string())
+    }
+}
 
 pub struct BlogPreviewListDisplayer;
 
-impl Displayer<Json<Result<Articles, Error>>, usize> for BlogPreviewListDisplayer {
-    fn display(value: &Option<Json<Result<Articles, Error>>>, page_number: usize) -> Html {
-        let (start_index, end_index) = calculate_indexes_from_page(page_number);
+impl Displayer<Json<Result<Articles, Error>>, ListView> for BlogPreviewListDisplayer {
+    fn display(value: &Option<Json<Result<Articles, Error>>>, view: ListView) -> Html {
+        let ListView {
+            page_number,
+            sort,
+            infinite_scroll,
+        } = view;
         match value {
             Some(json) => match &json.0 {
+                Ok(arts) if infinite_scroll => html! {
+                    <InfiniteList
+                        articles=arts.articles.clone()
+                        page_size=arts.page_size
+                        page_number=page_number
+                        sort=sort
+                        extensions=arts.extensions
+                    />
+                },
                 Ok(arts) => {
-                    html! {
-                        <>
-                            <div class="row">
-                                {
-                                    for arts.articles[start_index..min(arts.articles.len(), end_index)].iter().map(|article| {
-                                        display_article(article)
-                                    })
-
-                                }
-                            </div>
-                            <div class="row">
-                                {
-                                    if page_number > 1 {
-                                        html! {
-                                            <RouterAnchor<AppRoute> classes="col-1 pageButton bg-element-dark" route={AppRoute::Page(page_number - 1)}>
-                                                <div class="pageButton">
-                                                    {"<<"}
-                                                </div>
-                                            </RouterAnchor<AppRoute>>
-                                        }
-                                    } else {
-                                        html! {}
-                                    }
-                                }
-                                {
-                                    if end_index < arts.articles.len() {
-                                        html! {
-                                            <RouterAnchor<AppRoute> classes="col-1 offset-10 pageButton bg-element-dark"  route={AppRoute::Page(page_number + 1)}>
-                                                <div class="pageButton">
-                                                    {">>"}
-                                                </div>
-                                            </RouterAnchor<AppRoute>>
-                                        }
-                                    } else {
-                                        html! {}
+                    let page_count = page_count(arts.articles.len(), arts.page_size);
+                    match page_indexes(page_number, arts.articles.len(), arts.page_size) {
+                        Some((start_index, end_index)) => html! {
+                            <>
+                                <div class="row">
+                                    {
+                                        for arts.articles[start_index..end_index].iter().map(|article| {
+                                            display_article(article, arts.extensions)
+                                        })
                                     }
-                                }
-                            </div>
-                        </>
+                                </div>
+                                <Pagination page_number=page_number page_count=page_count sort=sort/>
+                            </>
+                        },
+                        None => page_not_found(page_number, page_count, sort),
                     }
                 }
-                _ => html! {<p>{"Error"}</p>},
+                _ => view_invalid_response(),
             },
             None => spinner(),
         }
     }
 }
 
-fn display_article(article: &str) -> Html {
+pub fn page_not_found(page_number: usize, page_count: usize, sort: SortOrder) -> Html {
+    html! {
+        <div class="bg-element-dark text-element-white page-not-found">
+            <p>{format!("Page {} not found, there are {} pages.", page_number, page_count)}</p>
+            <RouterAnchor<AppRoute> route={page_route(1, sort)}>
+                {"Go to the first page"}
+            </RouterAnchor<AppRoute>>
+        </div>
+    }
+}
+
+pub fn display_article(article: &ArticleMetadata, extensions: Extensions) -> Html {
+    let stats = match article.date {
+        Some(date) => format!("{} · {}", format_date(date), reading_stats(article)),
+        None => reading_stats(article),
+    };
+    let view = ArticleView {
+        extensions,
+        ..ArticleView::default()
+    };
     html! {
         <div class="container rounded previewer" style="margin-top: 1%; display: -webkit-box; -webkit-box-orient: vertical;">
-            <RouterAnchor<AppRoute>  route={AppRoute::ViewPost(article.to_string())}>
+            <RouterAnchor<AppRoute>  route={AppRoute::ViewPost(article.name.clone())}>
+                <p class="reading-stats">{stats}</p>
                 <div style="-webkit-line-clamp: 8; overflow: hidden; display: -webkit-box; -webkit-box-orient: vertical;">
-                    <BlogDisplayerComponent  url={("/preview/articles/".to_string() + article)} />
+                    <BlogDisplayerComponent  url={("/preview/articles/".to_string() + &article.name)} extra_args=view />
                 </div>
             </RouterAnchor<AppRoute>>
         </div>
     }
 }
 
-fn calculate_indexes_from_page(page_number: usize) -> (usize, usize) {
-    let start_index = (page_number - 1) * PAGE_SIZE;
-    let end_index = start_index + PAGE_SIZE;
-    (start_index, end_index)
+pub struct Pagination {
+    props: PaginationProps,
+    link: ComponentLink<Self>,
+    router: RouteAgentDispatcher<()>,
+    // What's typed in the jump to page input
+    jump_to: String,
+}
+
+#[derive(Properties, Debug, Clone, PartialEq)]
+pub struct PaginationProps {
+    pub page_number: usize,
+    pub page_count: usize,
+    pub sort: SortOrder,
+}
+
+pub enum PaginationMessage {
+    JumpTo(String),
+    Jump,
+}
+
+impl Pagination {
+    fn page_button(&self, page_number: usize, text: String) -> Html {
+        let active = if page_number == self.props.page_number {
+            "page-item active"
+        } else {
+            "page-item"
+        };
+        html! {
+            <li class=active>
+                <RouterAnchor<AppRoute> classes="page-link" route={page_route(page_number, self.props.sort)}>
+                    {text}
+                </RouterAnchor<AppRoute>>
+            </li>
+        }
+    }
+
+    fn view_link(&self, link: PageLink) -> Html {
+        match link {
+            PageLink::Page(page_number) => self.page_button(page_number, page_number.to_string()),
+            PageLink::Ellipsis => html! {
+                <li class="page-item disabled"><span class="page-link">{"…"}</span></li>
+            },
+        }
+    }
+}
+
+impl Component for Pagination {
+    type Properties = PaginationProps;
+    type Message = PaginationMessage;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Pagination {
+            props,
+            link,
+            router: RouteAgentDispatcher::new(),
+            jump_to: String::new(),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            PaginationMessage::JumpTo(jump_to) => {
+                self.jump_to = jump_to;
+                false
+            }
+            // Pages that don't exist are reported by the list like any other bad page number
+            PaginationMessage::Jump => {
+                if let Ok(page_number) = self.jump_to.trim().parse() {
+                    self.router
+                        .send(RouteRequest::ChangeRoute(Route::from(page_route(
+                            page_number,
+                            self.props.sort,
+                        ))));
+                }
+                self.jump_to.clear();
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let PaginationProps {
+            page_number,
+            page_count,
+            ..
+        } = self.props;
+        html! {
+            <nav class="row pagination-control">
+                <ul class="pagination pagination-sm">
+                    {
+                        if page_number > 1 {
+                            html! {
+                                <>
+                                    {self.page_button(1, "«".to_string())}
+                                    {self.page_button(page_number - 1, "‹".to_string())}
+                                </>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    {for page_links(page_number, page_count, PAGE_WINDOW).into_iter().map(|link| self.view_link(link))}
+                    {
+                        if page_number < page_count {
+                            html! {
+                                <>
+                                    {self.page_button(page_number + 1, "›".to_string())}
+                                    {self.page_button(page_count, "»".to_string())}
+                                </>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </ul>
+                <span class="page-count">{format!("Page {} of {}", page_number, page_count)}</span>
+                <form
+                    class="page-jump"
+                    onsubmit=self.link.callback(|e: FocusEvent| {
+                        e.prevent_default();
+                        PaginationMessage::Jump
+                    })
+                >
+                    <input
+                        class="form-control form-control-sm"
+                        type="number"
+                        min="1"
+                        max=page_count.to_string()
+                        placeholder="Page"
+                        value=self.jump_to.clone()
+                        oninput=self.link.callback(|e: InputData| PaginationMessage::JumpTo(e.value))
+                    />
+                    <button class="btn btn-sm btn-outline-light" type="submit">{"Go"}</button>
+                </form>
+            </nav>
+        }
+    }
+}
+
+fn stored_infinite_scroll() -> bool {
+    StorageService::new(Area::Local)
+        .ok()
+        .and_then(|storage| {
+            storage
+                .restore::<Result<String, Error>>(INFINITE_SCROLL_KEY)
+                .ok()
+        })
+        .as_deref()
+        == Some("true")
+}
+
+// The home page list with its options, changing them doesn't fetch the list again
+pub struct ArticleList {
+    props: ArticleListProps,
+    link: ComponentLink<Self>,
+    infinite_scroll: bool,
+}
+
+#[derive(Properties, Debug, Clone, PartialEq)]
+pub struct ArticleListProps {
+    pub page_number: usize,
+    pub sort: SortOrder,
+}
+
+pub enum ArticleListMessage {
+    ToggleInfiniteScroll,
+}
+
+impl Component for ArticleList {
+    type Properties = ArticleListProps;
+    type Message = ArticleListMessage;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        ArticleList {
+            props,
+            link,
+            infinite_scroll: stored_infinite_scroll(),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let ArticleListMessage::ToggleInfiniteScroll = msg;
+        self.infinite_scroll = !self.infinite_scroll;
+        if let Ok(mut storage) = StorageService::new(Area::Local) {
+            storage.store(
+                INFINITE_SCROLL_KEY,
+                Ok::<String, Error>(self.infinite_scroll.to_string()),
+            );
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let ArticleListProps { page_number, sort } = self.props;
+        html! {
+            <>
+                <div class="row list-options">
+                    <div class="custom-control custom-switch">
+                        <input
+                            type="checkbox"
+                            class="custom-control-input"
+                            id="infinite-scroll"
+                            checked=self.infinite_scroll
+                            onclick=self.link.callback(|_| ArticleListMessage::ToggleInfiniteScroll)
+                        />
+                        <label class="custom-control-label" for="infinite-scroll">
+                            {"Infinite scroll"}
+                        </label>
+                    </div>
+                    <SortSelector sort=sort/>
+                </div>
+                <BlogPreviewListDisplayerComponent
+                    url=format!("{}?sort={}", constants::ARTICLE_LIST_URI, sort)
+                    extra_args=ListView { page_number, sort, infinite_scroll: self.infinite_scroll }
+                />
+            </>
+        }
+    }
+}
+
+pub struct SortSelector {
+    props: SortSelectorProps,
+    link: ComponentLink<Self>,
+    router: RouteAgentDispatcher<()>,
+}
+
+#[derive(Properties, Debug, Clone, PartialEq)]
+pub struct SortSelectorProps {
+    pub sort: SortOrder,
+}
+
+pub enum SortSelectorMessage {
+    Select(String),
+    Ignore,
+}
+
+impl Component for SortSelector {
+    type Properties = SortSelectorProps;
+    type Message = SortSelectorMessage;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        SortSelector {
+            props,
+            link,
+            router: RouteAgentDispatcher::new(),
+        }
+    }
+
+    // Sorting differently starts over from the first page
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        if let SortSelectorMessage::Select(sort) = msg {
+            let sort = sort.parse().unwrap_or_default();
+            self.router
+                .send(RouteRequest::ChangeRoute(Route::from(page_route(1, sort))));
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <select
+                class="custom-select custom-select-sm bg-element-dark text-element-white sort-selector"
+                onchange=self.link.callback(|e: ChangeData| match e {
+                    ChangeData::Select(select) => SortSelectorMessage::Select(select.value()),
+                    _ => SortSelectorMessage::Ignore,
+                })
+            >
+                {
+                    for SortOrder::ALL.iter().map(|sort| html! {
+                        <option value=sort.as_str() selected=*sort == self.props.sort>
+                            {sort.label()}
+                        </option>
+                    })
+                }
+            </select>
+        }
+    }
 }
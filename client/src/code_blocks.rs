@@ -0,0 +1,170 @@
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use yew::prelude::*;
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use yew::{web_sys, App};
+
+const COPIED_FEEDBACK: Duration = Duration::from_secs(2);
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["navigator", "clipboard"], js_name = writeText)]
+    fn write_text(text: &str) -> js_sys::Promise;
+}
+
+// Unmounts a component mounted by `mount_components` when emitted
+pub type Unmount = Callback<()>;
+
+// Yew can't destroy an app mounted on its own, so the components are mounted in this, which
+// renders nothing once it gets a message. That drops the component and its listeners, which are
+// what keeps the app alive.
+struct Mounted<COMP: Component> {
+    props: COMP::Properties,
+    unmounted: bool,
+}
+
+impl<COMP: Component> Component for Mounted<COMP> {
+    type Properties = COMP::Properties;
+    type Message = ();
+
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        Mounted {
+            props,
+            unmounted: false,
+        }
+    }
+
+    fn update(&mut self, _: Self::Message) -> ShouldRender {
+        self.unmounted = true;
+        true
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        if self.unmounted {
+            return html! {};
+        }
+        let props = self.props.clone();
+        html! {<COMP with props />}
+    }
+}
+
+// Articles are set as inner HTML, so components are mounted on the elements the markdown renderer
+// marked. Mounting replaces the element's children, so each component gets a new one appended.
+// Whoever shows the container has to unmount them when it's gone.
+pub fn mount_components<COMP, F>(
+    container: &web_sys::Element,
+    selector: &str,
+    props: F,
+) -> Vec<Unmount>
+where
+    COMP: Component,
+    F: Fn(&web_sys::Element) -> COMP::Properties,
+{
+    let mut unmounts = Vec::new();
+    let document = match web_sys::window().and_then(|window| window.document()) {
+        Some(document) => document,
+        None => return unmounts,
+    };
+
+    let elements = match container.query_selector_all(selector) {
+        Ok(elements) => elements,
+        Err(_) => return unmounts,
+    };
+    for i in 0..elements.length() {
+        let element = match elements
+            .get(i)
+            .and_then(|node| node.dyn_into::<web_sys::Element>().ok())
+        {
+            Some(element) => element,
+            None => continue,
+        };
+
+        let mount_point = document.create_element("div").expect("Couldn't create div");
+        if element.append_child(&mount_point).is_ok() {
+            let link = App::<Mounted<COMP>>::new().mount_with_props(mount_point, props(&element));
+            unmounts.push(link.callback(|_| ()));
+        }
+    }
+    unmounts
+}
+
+pub struct CopyButton {
+    props: CopyButtonProps,
+    link: ComponentLink<Self>,
+    // Shows "Copied" until it's done
+    timeout_task: Option<TimeoutTask>,
+}
+
+#[derive(Properties, Debug, Clone, PartialEq)]
+pub struct CopyButtonProps {
+    pub code: String,
+}
+
+pub enum CopyButtonMessage {
+    Copy,
+    Reset,
+}
+
+impl Component for CopyButton {
+    type Properties = CopyButtonProps;
+    type Message = CopyButtonMessage;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        CopyButton {
+            props,
+            link,
+            timeout_task: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            CopyButtonMessage::Copy => {
+                let _ = write_text(&self.props.code);
+                self.timeout_task = Some(TimeoutService::spawn(
+                    COPIED_FEEDBACK,
+                    self.link.callback(|_| CopyButtonMessage::Reset),
+                ));
+            }
+            CopyButtonMessage::Reset => self.timeout_task = None,
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <button
+                class="btn btn-sm btn-outline-light code-copy"
+                onclick=self.link.callback(|_| CopyButtonMessage::Copy)
+            >
+                { if self.timeout_task.is_some() { "Copied" } else { "Copy" } }
+            </button>
+        }
+    }
+}
+
+// The copied code is what's shown, without line numbers nor rustdoc hidden lines
+pub fn mount_copy_buttons(container: &web_sys::Element) -> Vec<Unmount> {
+    mount_components::<CopyButton, _>(container, ".code-block", |block| CopyButtonProps {
+        code: block
+            .query_selector("code")
+            .ok()
+            .flatten()
+            .and_then(|code| code.text_content())
+            .unwrap_or_default(),
+    })
+}
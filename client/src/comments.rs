@@ -0,0 +1,235 @@
+use crate::date::format_date;
+use crate::errors::{view_error, view_invalid_response, FetchError};
+use crate::spinner::spinner;
+use anyhow::Error;
+use http::{Request, Response};
+use shared::comments::{CommentThread, Comments, NewComment};
+use shared::constants;
+use yew::format::{Json, Nothing, Text};
+use yew::prelude::*;
+use yew::services::{fetch::FetchTask, FetchService};
+
+pub struct CommentsSection {
+    props: CommentsProps,
+    link: ComponentLink<Self>,
+    comments: Option<Result<Comments, Error>>,
+    // Failed responses never get to `comments`, only those with a successful status do
+    error: Option<FetchError>,
+    fetch_task: FetchTask,
+    post_task: Option<FetchTask>,
+    author: String,
+    body: String,
+    reply_to: Option<u64>,
+    feedback: Option<String>,
+}
+
+#[derive(Properties, Debug, Clone, PartialEq)]
+pub struct CommentsProps {
+    pub article: String,
+}
+
+pub enum CommentsMessage {
+    Loaded(Result<Comments, Error>),
+    Failed(FetchError),
+    Retry,
+    Author(String),
+    Body(String),
+    ReplyTo(Option<u64>),
+    Submit,
+    Posted(Result<(), String>),
+}
+
+fn comments_url(article: &str) -> String {
+    format!("{}/{}", constants::COMMENTS_URI, article)
+}
+
+fn fetch_comments(article: &str, link: &ComponentLink<CommentsSection>) -> FetchTask {
+    let get_req = Request::get(comments_url(article)).body(Nothing).unwrap();
+    let callback = link.callback(|response: Response<Text>| {
+        let (parts, body) = response.into_parts();
+        if parts.status.is_success() {
+            CommentsMessage::Loaded(Json::from(body).0)
+        } else {
+            CommentsMessage::Failed(FetchError::from_response(parts.status, body))
+        }
+    });
+
+    FetchService::fetch(get_req, callback).unwrap()
+}
+
+impl Component for CommentsSection {
+    type Properties = CommentsProps;
+    type Message = CommentsMessage;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let fetch_task = fetch_comments(&props.article, &link);
+        CommentsSection {
+            props,
+            link,
+            comments: None,
+            error: None,
+            fetch_task,
+            post_task: None,
+            author: String::new(),
+            body: String::new(),
+            reply_to: None,
+            feedback: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            CommentsMessage::Loaded(comments) => self.comments = Some(comments),
+            CommentsMessage::Failed(error) => self.error = Some(error),
+            CommentsMessage::Retry => {
+                self.error = None;
+                self.fetch_task = fetch_comments(&self.props.article, &self.link);
+            }
+            CommentsMessage::Author(author) => self.author = author,
+            CommentsMessage::Body(body) => self.body = body,
+            CommentsMessage::ReplyTo(reply_to) => self.reply_to = reply_to,
+            CommentsMessage::Submit => {
+                let new_comment = NewComment {
+                    parent: self.reply_to,
+                    author: self.author.clone(),
+                    body: self.body.clone(),
+                };
+                let post_req = Request::post(comments_url(&self.props.article))
+                    .header("Content-Type", "application/json")
+                    .body(Json(&new_comment))
+                    .unwrap();
+                let callback = self.link.callback(|response: Response<Text>| {
+                    let (parts, body) = response.into_parts();
+                    CommentsMessage::Posted(if parts.status.is_success() {
+                        Ok(())
+                    } else {
+                        Err(FetchError::from_response(parts.status, body).to_string())
+                    })
+                });
+                self.post_task = Some(FetchService::fetch(post_req, callback).unwrap());
+            }
+            CommentsMessage::Posted(result) => {
+                self.post_task = None;
+                match result {
+                    Ok(()) => {
+                        self.body = String::new();
+                        self.reply_to = None;
+                        self.feedback = Some(
+                            "Thanks! Your comment will show up once it's approved".to_string(),
+                        );
+                    }
+                    Err(e) => self.feedback = Some(e),
+                }
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            self.comments = None;
+            self.error = None;
+            self.reply_to = None;
+            self.feedback = None;
+            self.fetch_task = fetch_comments(&self.props.article, &self.link);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="comments bg-element-dark text-element-white">
+                <b style="font-weight: 1000;">{"Comments"}</b>
+                {
+                    match (&self.error, &self.comments) {
+                        (Some(error), _) => {
+                            view_error(error, self.link.callback(|_| CommentsMessage::Retry))
+                        }
+                        (None, Some(Ok(comments))) if comments.threads.is_empty() => html! {
+                            <p>{"No comments yet, be the first one!"}</p>
+                        },
+                        (None, Some(Ok(comments))) => html! {
+                            <>
+                                { for comments.threads.iter().map(|thread| self.view_thread(thread)) }
+                            </>
+                        },
+                        (None, Some(Err(_))) => view_invalid_response(),
+                        (None, None) => spinner(),
+                    }
+                }
+                {self.view_form()}
+            </div>
+        }
+    }
+}
+
+impl CommentsSection {
+    fn view_thread(&self, thread: &CommentThread) -> Html {
+        let id = thread.comment.id;
+        html! {
+            <div class="comment">
+                <p class="comment-header">
+                    <b>{&thread.comment.author}</b>
+                    {format!(" · {}", format_date(thread.comment.timestamp))}
+                </p>
+                <p class="comment-body">{&thread.comment.body}</p>
+                <button class="btn btn-link btn-sm" onclick=self.link.callback(move |_| CommentsMessage::ReplyTo(Some(id)))>
+                    {"Reply"}
+                </button>
+                <div class="comment-replies">
+                    { for thread.replies.iter().map(|reply| self.view_thread(reply)) }
+                </div>
+            </div>
+        }
+    }
+
+    fn view_form(&self) -> Html {
+        html! {
+            <div class="comment-form">
+                {
+                    match self.reply_to {
+                        Some(id) => html! {
+                            <p>
+                                {format!("Replying to comment #{} ", id)}
+                                <button class="btn btn-link btn-sm" onclick=self.link.callback(|_| CommentsMessage::ReplyTo(None))>
+                                    {"cancel"}
+                                </button>
+                            </p>
+                        },
+                        None => html! {},
+                    }
+                }
+                <input
+                    class="form-control"
+                    placeholder="Name"
+                    maxlength=constants::MAX_COMMENT_AUTHOR_LENGTH.to_string()
+                    value=self.author.clone()
+                    oninput=self.link.callback(|e: InputData| CommentsMessage::Author(e.value))
+                />
+                <textarea
+                    class="form-control"
+                    placeholder="Leave a comment"
+                    maxlength=constants::MAX_COMMENT_LENGTH.to_string()
+                    value=self.body.clone()
+                    oninput=self.link.callback(|e: InputData| CommentsMessage::Body(e.value))
+                />
+                <button
+                    class="btn btn-outline-light"
+                    disabled=self.post_task.is_some()
+                    onclick=self.link.callback(|_| CommentsMessage::Submit)
+                >
+                    {"Send"}
+                </button>
+                {
+                    match &self.feedback {
+                        Some(feedback) => html! {<p>{feedback}</p>},
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use wasm_bindgen::JsValue;
+
+// Timestamps coming from the server are seconds since the unix epoch
+pub fn format_date(timestamp: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(timestamp as f64 * 1000.0));
+    date.to_date_string().into()
+}
@@ -0,0 +1,101 @@
+use crate::routes::AppRoute;
+use anyhow::Error;
+use http::StatusCode;
+use shared::errors::ApiError;
+use std::fmt;
+use yew::format::{Json, Text};
+use yew::prelude::*;
+use yew_router::components::RouterAnchor;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    NotFound(String),
+    // The server couldn't be reached at all
+    Network,
+    Server {
+        status: u16,
+        message: String,
+        id: Option<String>,
+    },
+}
+
+impl FetchError {
+    // The server answers errors with an `ApiError`, anything else(e.g. a proxy's error page) only
+    // has the status to go by
+    pub fn from_response(status: StatusCode, body: Text) -> FetchError {
+        let body = match body {
+            Ok(body) => body,
+            Err(_) => return FetchError::Network,
+        };
+
+        let api_error = Json::<Result<ApiError, Error>>::from(Ok(body)).0.ok();
+        let message = match &api_error {
+            Some(api_error) => api_error.message.clone(),
+            None => status.to_string(),
+        };
+        if status == StatusCode::NOT_FOUND {
+            FetchError::NotFound(message)
+        } else {
+            FetchError::Server {
+                status: status.as_u16(),
+                message,
+                id: api_error.and_then(|api_error| api_error.id),
+            }
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::NotFound(message) => f.write_str(message),
+            FetchError::Network => f.write_str("Couldn't reach the server"),
+            FetchError::Server {
+                message,
+                id: Some(id),
+                ..
+            } => write!(f, "{} (error id {})", message, id),
+            FetchError::Server { message, .. } => f.write_str(message),
+        }
+    }
+}
+
+// Answers that can't be read, there's no point in retrying those
+pub fn view_invalid_response() -> Html {
+    html! {
+        <div class="bg-element-dark text-element-white fetch-error">
+            <h5>{"Something went wrong"}</h5>
+            <p>{"The server sent something we can't read."}</p>
+        </div>
+    }
+}
+
+// Retrying only makes sense when the thing exists
+pub fn view_error(error: &FetchError, retry: Callback<MouseEvent>) -> Html {
+    let (title, action) = match error {
+        FetchError::NotFound(_) => (
+            "Not found",
+            html! {
+                <RouterAnchor<AppRoute> route={AppRoute::HomePage}>
+                    {"Back to the articles"}
+                </RouterAnchor<AppRoute>>
+            },
+        ),
+        FetchError::Network => ("You seem to be offline", retry_button(retry)),
+        FetchError::Server { .. } => ("Something went wrong", retry_button(retry)),
+    };
+
+    html! {
+        <div class="bg-element-dark text-element-white fetch-error">
+            <h5>{title}</h5>
+            <p>{error.to_string()}</p>
+            {action}
+        </div>
+    }
+}
+
+fn retry_button(retry: Callback<MouseEvent>) -> Html {
+    html! {
+        <button class="btn btn-sm btn-outline-light" onclick=retry>{"Retry"}</button>
+    }
+}
@@ -0,0 +1,178 @@
+use crate::blog_displayer::scroll_to;
+use crate::blog_preview_list::{display_article, page_not_found, page_route};
+use crate::spinner::spinner;
+use shared::article_list::{ArticleMetadata, SortOrder};
+use shared::markdown::Extensions;
+use shared::pagination_indexes::{page_count, page_indexes};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use yew::prelude::*;
+use yew::web_sys::{
+    self, IntersectionObserver, IntersectionObserverEntry, IntersectionObserverInit,
+};
+use yew_router::agent::{RouteAgentDispatcher, RouteRequest};
+use yew_router::route::Route;
+
+// How far below the screen the end of the list is when the next page is shown, so the previews
+// have time to load
+const NEAR_BOTTOM_MARGIN: &str = "0px 0px 400px 0px";
+
+// Shows every page up to the one in the url, which is moved to the next page when the reader gets
+// near the end of the list. The url always has the last page shown so it can be shared.
+pub struct InfiniteList {
+    props: InfiniteListProps,
+    link: ComponentLink<Self>,
+    router: RouteAgentDispatcher<()>,
+    // Placed after the last preview, it's what the observer watches
+    end: NodeRef,
+    observer: Option<IntersectionObserver>,
+    // Called by the observer, so it has to live as long as it
+    on_intersection: Option<Closure<dyn FnMut(js_sys::Array)>>,
+}
+
+#[derive(Properties, Debug, Clone, PartialEq)]
+pub struct InfiniteListProps {
+    pub articles: Vec<ArticleMetadata>,
+    pub page_size: usize,
+    pub page_number: usize,
+    pub sort: SortOrder,
+    pub extensions: Extensions,
+}
+
+pub enum InfiniteListMessage {
+    NearEnd,
+}
+
+fn page_anchor(page_number: usize) -> String {
+    format!("page-{}", page_number)
+}
+
+impl InfiniteList {
+    fn observe(&mut self) {
+        if self.observer.is_none() {
+            let link = self.link.clone();
+            let on_intersection = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+                let near_end = entries.iter().any(|entry| {
+                    entry
+                        .dyn_into::<IntersectionObserverEntry>()
+                        .map(|entry| entry.is_intersecting())
+                        .unwrap_or(false)
+                });
+                if near_end {
+                    link.send_message(InfiniteListMessage::NearEnd);
+                }
+            }) as Box<dyn FnMut(js_sys::Array)>);
+
+            let mut options = IntersectionObserverInit::new();
+            options.root_margin(NEAR_BOTTOM_MARGIN);
+            self.observer = IntersectionObserver::new_with_options(
+                on_intersection.as_ref().unchecked_ref(),
+                &options,
+            )
+            .ok();
+            self.on_intersection = Some(on_intersection);
+        }
+
+        // Observing again reports whether the end is still near, which it is when the new page
+        // doesn't fill the screen
+        if let (Some(observer), Some(end)) = (&self.observer, self.end.cast::<web_sys::Element>()) {
+            observer.unobserve(&end);
+            observer.observe(&end);
+        }
+    }
+}
+
+impl Component for InfiniteList {
+    type Properties = InfiniteListProps;
+    type Message = InfiniteListMessage;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        InfiniteList {
+            props,
+            link,
+            router: RouteAgentDispatcher::new(),
+            end: NodeRef::default(),
+            observer: None,
+            on_intersection: None,
+        }
+    }
+
+    // Replacing the route instead of pushing a new one, going back shouldn't go page by page
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let InfiniteListMessage::NearEnd = msg;
+        let InfiniteListProps {
+            articles,
+            page_size,
+            page_number,
+            sort,
+            ..
+        } = &self.props;
+        let (_, end_index) =
+            page_indexes(*page_number, articles.len(), *page_size).unwrap_or_default();
+        if end_index < articles.len() {
+            self.router
+                .send(RouteRequest::ReplaceRoute(Route::from(page_route(
+                    page_number + 1,
+                    *sort,
+                ))));
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    // A shared url opens where the reader who shared it was
+    fn rendered(&mut self, first_render: bool) {
+        if first_render && self.props.page_number > 1 {
+            scroll_to(page_anchor(self.props.page_number));
+        }
+        self.observe();
+    }
+
+    fn destroy(&mut self) {
+        if let Some(observer) = &self.observer {
+            observer.disconnect();
+        }
+    }
+
+    fn view(&self) -> Html {
+        let InfiniteListProps {
+            articles,
+            page_size,
+            page_number,
+            sort,
+            extensions,
+        } = &self.props;
+        let end_index = match page_indexes(*page_number, articles.len(), *page_size) {
+            Some((_, end_index)) => end_index,
+            None => {
+                return page_not_found(*page_number, page_count(articles.len(), *page_size), *sort)
+            }
+        };
+
+        html! {
+            <>
+                <div class="row">
+                    {
+                        for articles[..end_index].chunks(*page_size).enumerate().map(|(i, page)| html! {
+                            <>
+                                <div id=page_anchor(i + 1) class="page-anchor"></div>
+                                {for page.iter().map(|article| display_article(article, *extensions))}
+                            </>
+                        })
+                    }
+                </div>
+                <div ref=self.end.clone() class="row infinite-scroll-end">
+                    { if end_index < articles.len() { spinner() } else { html! {} } }
+                </div>
+            </>
+        }
+    }
+}
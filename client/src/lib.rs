@@ -7,12 +7,23 @@ use wasm_bindgen::prelude::*;
 use yew::prelude::*;
 
 mod about_me;
+mod archive;
 mod blog_displayer;
 mod blog_preview_list;
+mod code_blocks;
+mod comments;
+mod date;
+mod errors;
+mod infinite_scroll;
+mod playground;
 mod request_loader;
+mod revisions;
 mod root;
 mod routes;
+mod site;
 mod spinner;
+mod stats;
+mod webmentions;
 
 #[wasm_bindgen(start)]
 pub async fn run_app() {
@@ -0,0 +1,128 @@
+use crate::code_blocks::{mount_components, Unmount};
+use crate::errors::FetchError;
+use anyhow::Error;
+use http::{Request, Response};
+use shared::constants;
+use shared::playground::{Execution, ExecutionOutput};
+use yew::format::{Json, Text};
+use yew::prelude::*;
+use yew::services::{fetch::FetchTask, FetchService};
+use yew::web_sys;
+
+// Run button and output under a runnable code block
+pub struct Playground {
+    props: PlaygroundProps,
+    link: ComponentLink<Self>,
+    fetch_task: Option<FetchTask>,
+    output: Option<Result<ExecutionOutput, String>>,
+}
+
+#[derive(Properties, Debug, Clone, PartialEq)]
+pub struct PlaygroundProps {
+    pub code: String,
+}
+
+pub enum PlaygroundMessage {
+    Run,
+    Ran(Result<ExecutionOutput, String>),
+}
+
+fn view_output(output: &ExecutionOutput) -> Html {
+    let stream = |class: &str, text: &str| {
+        if text.is_empty() {
+            html! {}
+        } else {
+            html! { <pre class=class>{text.to_string()}</pre> }
+        }
+    };
+
+    html! {
+        <>
+            {stream("playground-stderr", &output.stderr)}
+            {stream("playground-stdout", &output.stdout)}
+        </>
+    }
+}
+
+impl Component for Playground {
+    type Properties = PlaygroundProps;
+    type Message = PlaygroundMessage;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Playground {
+            props,
+            link,
+            fetch_task: None,
+            output: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            PlaygroundMessage::Run => {
+                let execution = Execution {
+                    code: self.props.code.clone(),
+                };
+                let post_req = Request::post(constants::PLAYGROUND_URI)
+                    .header("Content-Type", "application/json")
+                    .body(Json(&execution))
+                    .unwrap();
+                let callback = self.link.callback(|response: Response<Text>| {
+                    let (parts, body) = response.into_parts();
+                    PlaygroundMessage::Ran(if parts.status.is_success() {
+                        Json::<Result<ExecutionOutput, Error>>::from(body)
+                            .0
+                            .map_err(|e| e.to_string())
+                    } else {
+                        Err(FetchError::from_response(parts.status, body).to_string())
+                    })
+                });
+                self.fetch_task = Some(FetchService::fetch(post_req, callback).unwrap());
+            }
+            PlaygroundMessage::Ran(output) => {
+                self.fetch_task = None;
+                self.output = Some(output);
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            self.output = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let running = self.fetch_task.is_some();
+        html! {
+            <div class="playground-controls">
+                <button
+                    class="btn btn-sm btn-outline-light"
+                    disabled=running
+                    onclick=self.link.callback(|_| PlaygroundMessage::Run)
+                >
+                    { if running { "Running..." } else { "Run" } }
+                </button>
+                {
+                    match &self.output {
+                        Some(Ok(output)) => view_output(output),
+                        Some(Err(e)) => html! {<p>{format!("Error: {}", e)}</p>},
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+}
+
+// Mounts a `Playground` on every block marked by the markdown renderer within `container`
+pub fn mount_playgrounds(container: &web_sys::Element) -> Vec<Unmount> {
+    mount_components::<Playground, _>(container, ".playground", |block| PlaygroundProps {
+        code: block.get_attribute("data-code").unwrap_or_default(),
+    })
+}
@@ -1,8 +1,10 @@
+use crate::errors::{view_error, FetchError};
 use http::{Request, Response};
+use shared::constants;
 use yew::format::Text;
 use yew::prelude::*;
 use yew::services::{fetch::FetchTask, FetchService};
-use yew::{format::Nothing, html, Component, ComponentLink, Html, ShouldRender};
+use yew::{format::Nothing, html, web_sys, Component, ComponentLink, Html, ShouldRender};
 
 pub struct RequestLoader<
     T: Displayer<U, V> + 'static,
@@ -12,11 +14,18 @@ pub struct RequestLoader<
     props: RequestLoaderProps<V>,
     fetch_task: FetchTask,
     display_value: Option<U>,
+    // Failed responses never get to the displayer, only those with a successful status do
+    error: Option<FetchError>,
     link: ComponentLink<Self>,
 }
 
 pub trait Displayer<U, T> {
     fn display(value: &Option<U>, extra_args: T) -> Html;
+
+    // Displayers of secondary information can leave errors out instead
+    fn display_error(error: &FetchError, retry: Callback<MouseEvent>) -> Html {
+        view_error(error, retry)
+    }
 }
 
 #[derive(Properties, Debug, Clone, PartialEq)]
@@ -28,6 +37,8 @@ pub struct RequestLoaderProps<T: Clone + Default + PartialEq> {
 
 pub enum FetchMessage<T> {
     Loaded(T),
+    Failed(FetchError),
+    Retry,
 }
 
 impl<
@@ -45,44 +56,71 @@ impl<
             props,
             fetch_task,
             display_value: None,
+            error: None,
             link,
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
-        let FetchMessage::Loaded(value) = msg;
-        self.display_value = Some(value);
+        match msg {
+            FetchMessage::Loaded(value) => self.display_value = Some(value),
+            FetchMessage::Failed(error) => self.error = Some(error),
+            FetchMessage::Retry => {
+                self.error = None;
+                self.fetch_task = fetch_link(&self.props.url, &self.link);
+            }
+        }
         true
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
-        if self.props != props {
+        if self.props == props {
+            return false;
+        }
+
+        // Only a new url needs a new request, different extra args just change how it's displayed
+        if self.props.url != props.url {
             self.display_value = None;
-            self.props = props;
-            self.fetch_task = fetch_link(&self.props.url, &self.link);
-            true
-        } else {
-            false
+            self.error = None;
+            self.fetch_task = fetch_link(&props.url, &self.link);
         }
+        self.props = props;
+        true
     }
 
     fn view(&self) -> Html {
-        html! {
-            {
-                T::display(&self.display_value, self.props.extra_args.clone())
-            }
+        match &self.error {
+            Some(error) => T::display_error(error, self.link.callback(|_| FetchMessage::Retry)),
+            None => T::display(&self.display_value, self.props.extra_args.clone()),
         }
     }
 }
 
+// Where the reader came from, the server only sees the blog itself as referrer
+fn page_referrer() -> String {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .map(|document| document.referrer())
+        .unwrap_or_default()
+}
+
 fn fetch_link<T: Displayer<U, V>, U: From<Text>, V: Default + PartialEq + 'static + Clone>(
     url: &str,
     link: &ComponentLink<RequestLoader<T, U, V>>,
 ) -> FetchTask {
-    let get_req = Request::get(url).body(Nothing).unwrap();
+    let get_req = Request::get(url)
+        .header(constants::PAGE_REFERRER_HEADER, page_referrer())
+        .body(Nothing)
+        .unwrap();
 
-    let callback =
-        link.callback(|response: Response<U>| FetchMessage::Loaded(response.into_body()));
+    let callback = link.callback(|response: Response<Text>| {
+        let (parts, body) = response.into_parts();
+        if parts.status.is_success() {
+            FetchMessage::Loaded(U::from(body))
+        } else {
+            FetchMessage::Failed(FetchError::from_response(parts.status, body))
+        }
+    });
 
     FetchService::fetch(get_req, callback).unwrap()
 }
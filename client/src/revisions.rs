@@ -0,0 +1,106 @@
+use crate::date::format_date;
+use crate::errors::{view_invalid_response, FetchError};
+use crate::request_loader::Displayer;
+use crate::request_loader::RequestLoader;
+use crate::routes::AppRoute;
+use crate::spinner::spinner;
+use anyhow::Error;
+use shared::revisions::{ChangeTag, Diff, RevisionDiff, Revisions};
+use yew::format::Json;
+use yew::prelude::*;
+use yew_router::components::RouterAnchor;
+
+pub type RevisionFooterComponent =
+    RequestLoader<RevisionFooter, Json<Result<Revisions, Error>>, ()>;
+
+pub type RevisionDiffComponent =
+    RequestLoader<RevisionDiffDisplayer, Json<Result<RevisionDiff, Error>>, ()>;
+
+pub struct RevisionFooter;
+
+impl Displayer<Json<Result<Revisions, Error>>, ()> for RevisionFooter {
+    fn display(value: &Option<Json<Result<Revisions, Error>>>, _: ()) -> Html {
+        match value {
+            Some(json) => match &json.0 {
+                Ok(revisions) => match revisions.revisions.last() {
+                    Some(latest) => html! {
+                        <div class="revision-footer text-element-white">
+                            {format!("Last updated {} by {}", format_date(latest.timestamp), latest.author)}
+                            {
+                                if revisions.revisions.len() > 1 {
+                                    html! {
+                                        <>
+                                            {" · "}
+                                            <RouterAnchor<AppRoute> route={AppRoute::ArticleChanges(revisions.article.clone())}>
+                                                {"view changes"}
+                                            </RouterAnchor<AppRoute>>
+                                        </>
+                                    }
+                                } else {
+                                    html! {}
+                                }
+                            }
+                        </div>
+                    },
+                    None => html! {},
+                },
+                // The footer is just extra information, no need to bother the reader if it fails
+                _ => html! {},
+            },
+            None => html! {},
+        }
+    }
+
+    fn display_error(_: &FetchError, _: Callback<MouseEvent>) -> Html {
+        html! {}
+    }
+}
+
+pub struct RevisionDiffDisplayer;
+
+impl Displayer<Json<Result<RevisionDiff, Error>>, ()> for RevisionDiffDisplayer {
+    fn display(value: &Option<Json<Result<RevisionDiff, Error>>>, _: ()) -> Html {
+        match value {
+            Some(json) => match &json.0 {
+                Ok(diff) => html! {
+                    <div class="bg-element-dark text-element-white">
+                        <RouterAnchor<AppRoute> route={AppRoute::ViewPost(diff.article.clone())}>
+                            {"<< Back to the article"}
+                        </RouterAnchor<AppRoute>>
+                        <p>
+                            {format!(
+                                "Changes between revision {} ({}) and revision {} ({})",
+                                diff.from.id,
+                                format_date(diff.from.timestamp),
+                                diff.to.id,
+                                format_date(diff.to.timestamp),
+                            )}
+                        </p>
+                        {view_diff(&diff.diff)}
+                    </div>
+                },
+                _ => view_invalid_response(),
+            },
+            None => spinner(),
+        }
+    }
+}
+
+fn view_diff(diff: &Diff) -> Html {
+    match diff {
+        Diff::Unified(text) => html! {
+            <pre class="revision-diff">{text}</pre>
+        },
+        Diff::Words(segments) => html! {
+            <pre class="revision-diff">
+                {
+                    for segments.iter().map(|segment| match segment.tag {
+                        ChangeTag::Equal => html! {<span>{&segment.value}</span>},
+                        ChangeTag::Insert => html! {<ins>{&segment.value}</ins>},
+                        ChangeTag::Delete => html! {<del>{&segment.value}</del>},
+                    })
+                }
+            </pre>
+        },
+    }
+}
@@ -1,7 +1,14 @@
-use crate::about_me::AboutMe;
-use crate::blog_displayer::BlogDisplayerComponent;
-use crate::blog_preview_list::BlogPreviewListDisplayerComponent;
+use crate::archive::ArchiveComponent;
+use crate::blog_displayer::{ArticleHeaderComponent, ArticleLinksComponent, ArticleView};
+use crate::blog_preview_list::ArticleList;
+use crate::revisions::{RevisionDiffComponent, RevisionFooterComponent};
 use crate::routes::AppRoute;
+use crate::site::{
+    AboutPageComponent, ArticleBodyComponent, ArticleExtrasComponent, AuthorPageComponent,
+    SiteHeadComponent,
+};
+use crate::stats::StatsDashboard;
+use shared::article_list::SortOrder;
 use shared::constants;
 use yew::{html, Component, ComponentLink, Html, ShouldRender};
 use yew_router::components::RouterAnchor;
@@ -30,6 +37,7 @@ impl Component for Root {
     fn view(&self) -> Html {
         html! {
             <body>
+                <SiteHeadComponent url=constants::SITE_URI/>
                 {header()}
                 {main_page()}
             </body>
@@ -37,10 +45,9 @@ impl Component for Root {
     }
 }
 
-fn display_page(page_number: usize) -> Html {
+fn display_page(page_number: usize, sort: SortOrder) -> Html {
     html! {
-        <BlogPreviewListDisplayerComponent url=constants::ARTICLE_LIST_URI extra_args = page_number/>
-
+        <ArticleList page_number=page_number sort=sort/>
     }
 }
 
@@ -56,14 +63,41 @@ fn main_page() -> Html {
     }
 }
 
+fn view_post(article: String, section: Option<String>) -> Html {
+    let view = ArticleView {
+        name: Some(article.clone()),
+        section,
+        ..ArticleView::default()
+    };
+    html! {
+        <>
+            <ArticleHeaderComponent url={format!("{}/{}", constants::ARTICLE_METADATA_URI, &article[..])}/>
+            <ArticleBodyComponent url=constants::SITE_URI extra_args=view/>
+            <ArticleLinksComponent url={format!("{}/{}", constants::ARTICLE_METADATA_URI, &article[..])}/>
+            <RevisionFooterComponent url={format!("{}/{}", constants::REVISIONS_URI, &article[..])}/>
+            <ArticleExtrasComponent url=constants::SITE_URI extra_args=article.clone()/>
+        </>
+    }
+}
+
 fn routing(switch: AppRoute) -> Html {
     match switch {
-        AppRoute::ViewPost(article) => html! {
-            <BlogDisplayerComponent url={format!("/{}/{}", constants::ARTICLES_PATH,  &article[..])}/>
+        AppRoute::ViewPost(article) => view_post(article, None),
+        AppRoute::ViewPostSection(article, section) => view_post(article, Some(section)),
+        AppRoute::ArticleChanges(article) => html! {
+            <RevisionDiffComponent url={format!("{}/{}/diff?mode=words", constants::REVISIONS_URI, &article[..])}/>
         },
-        AppRoute::Page(page_number) => display_page(page_number),
-        AppRoute::AboutMe => html! {<AboutMe />},
-        AppRoute::HomePage => display_page(1),
+        AppRoute::SortedPage(page_number, sort) => {
+            display_page(page_number, sort.parse().unwrap_or_default())
+        }
+        AppRoute::Page(page_number) => display_page(page_number, SortOrder::default()),
+        AppRoute::AboutMe => html! {<AboutPageComponent url=constants::SITE_URI />},
+        AppRoute::Author(id) => html! {
+            <AuthorPageComponent url=constants::SITE_URI extra_args=id/>
+        },
+        AppRoute::Archive => html! {<ArchiveComponent url=constants::ARCHIVE_URI />},
+        AppRoute::Stats => html! {<StatsDashboard />},
+        AppRoute::HomePage => display_page(1, SortOrder::default()),
     }
 }
 
@@ -74,7 +108,14 @@ fn header() -> Html {
                     <RouterAnchor<AppRoute> route={AppRoute::HomePage} classes="col-3">
                             <img src="./imgs/kitten.png#logo" />
                     </RouterAnchor<AppRoute>>
-                    <div class="col-2 offset-6">
+                    <div class="col-1 offset-5">
+                        <div style="position: absolute; bottom: 0;">
+                            <RouterAnchor<AppRoute> route={AppRoute::Archive}>
+                                {"Archive"}
+                            </RouterAnchor<AppRoute>>
+                        </div>
+                    </div>
+                    <div class="col-2">
                         <a href="https://ko-fi.com/S6S529BSG" target="_blank" style="position: absolute; bottom: 0;">
                             {"Buy me a coffee ☕"}
                         </a>
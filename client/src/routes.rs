@@ -4,8 +4,21 @@ use yew_router::prelude::*;
 pub enum AppRoute {
     #[to = "/#about_me"]
     AboutMe,
+    #[to = "/#authors/{id}"]
+    Author(String),
+    #[to = "/#archive"]
+    Archive,
+    #[to = "/#stats"]
+    Stats,
+    #[to = "/#changes/{post_name}"]
+    ArticleChanges(String),
+    #[to = "/#articles/{post_name}/{section}"]
+    ViewPostSection(String, String),
     #[to = "/#articles/{post_name}"]
     ViewPost(String),
+    // Sorted any other way than the default, which is left out of the url
+    #[to = "/#page={page_number}&sort={sort}"]
+    SortedPage(usize, String),
     #[to = "/#page={page_number}"]
     Page(usize),
     #[to = "/"]
@@ -0,0 +1,154 @@
+use crate::about_me::{AboutMeComponent, AuthorProfileComponent};
+use crate::blog_displayer::{ArticleView, BlogDisplayerComponent};
+use crate::comments::CommentsSection;
+use crate::errors::{view_invalid_response, FetchError};
+use crate::request_loader::{Displayer, RequestLoader};
+use crate::spinner::spinner;
+use crate::webmentions::MentionsComponent;
+use anyhow::Error;
+use shared::constants;
+use shared::site::SiteInfo;
+use yew::format::Json;
+use yew::prelude::*;
+use yew::web_sys;
+
+pub type SiteHeadComponent = RequestLoader<SiteHead, Json<Result<SiteInfo, Error>>, ()>;
+
+pub type ArticleExtrasComponent =
+    RequestLoader<ArticleExtras, Json<Result<SiteInfo, Error>>, String>;
+
+pub type AboutPageComponent = RequestLoader<AboutPage, Json<Result<SiteInfo, Error>>, ()>;
+
+pub type ArticleBodyComponent =
+    RequestLoader<ArticleBody, Json<Result<SiteInfo, Error>>, ArticleView>;
+
+pub type AuthorPageComponent = RequestLoader<AuthorPage, Json<Result<SiteInfo, Error>>, String>;
+
+// Replaces the title in `index.html` with the site's one and adds the site's stylesheet, if it
+// has one
+pub struct SiteHead;
+
+impl Displayer<Json<Result<SiteInfo, Error>>, ()> for SiteHead {
+    fn display(value: &Option<Json<Result<SiteInfo, Error>>>, _: ()) -> Html {
+        let site = match value {
+            Some(Json(Ok(site))) => site,
+            _ => return html! {},
+        };
+        if let Some(document) = web_sys::window().and_then(|window| window.document()) {
+            document.set_title(&site.title);
+        }
+        match &site.theme {
+            Some(theme) => html! { <link rel="stylesheet" href=theme.clone()/> },
+            None => html! {},
+        }
+    }
+
+    fn display_error(_: &FetchError, _: Callback<MouseEvent>) -> Html {
+        html! {}
+    }
+}
+
+// What goes under an article, the features turned off in the server are left out
+pub struct ArticleExtras;
+
+impl Displayer<Json<Result<SiteInfo, Error>>, String> for ArticleExtras {
+    fn display(value: &Option<Json<Result<SiteInfo, Error>>>, article: String) -> Html {
+        let features = match value {
+            Some(Json(Ok(site))) => site.features,
+            _ => return html! {},
+        };
+        html! {
+            <>
+                {
+                    if features.webmentions {
+                        html! {
+                            <MentionsComponent url={format!("{}/{}", constants::WEBMENTIONS_URI, &article[..])}/>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    if features.comments {
+                        html! { <CommentsSection article={article.clone()}/> }
+                    } else {
+                        html! {}
+                    }
+                }
+            </>
+        }
+    }
+
+    fn display_error(_: &FetchError, _: Callback<MouseEvent>) -> Html {
+        html! {}
+    }
+}
+
+// The site's own about page, its authors' profiles for sites without it
+pub struct AboutPage;
+
+impl Displayer<Json<Result<SiteInfo, Error>>, ()> for AboutPage {
+    fn display(value: &Option<Json<Result<SiteInfo, Error>>>, _: ()) -> Html {
+        match value {
+            Some(Json(Ok(SiteInfo {
+                about: Some(about),
+                extensions,
+                ..
+            }))) => {
+                let view = ArticleView {
+                    extensions: *extensions,
+                    ..ArticleView::default()
+                };
+                html! { <BlogDisplayerComponent url=about.clone() extra_args=view/> }
+            }
+            Some(Json(Ok(site))) => html! {
+                <AboutMeComponent url=constants::AUTHORS_URI extra_args=site.extensions/>
+            },
+            Some(Json(Err(_))) => html! { <AboutMeComponent url=constants::AUTHORS_URI/> },
+            None => spinner(),
+        }
+    }
+
+    fn display_error(_: &FetchError, _: Callback<MouseEvent>) -> Html {
+        html! { <AboutMeComponent url=constants::AUTHORS_URI/> }
+    }
+}
+
+// An article's content, rendered with the site's extensions. The view has the article's name.
+pub struct ArticleBody;
+
+impl Displayer<Json<Result<SiteInfo, Error>>, ArticleView> for ArticleBody {
+    fn display(value: &Option<Json<Result<SiteInfo, Error>>>, view: ArticleView) -> Html {
+        match value {
+            Some(Json(Ok(site))) => {
+                let url = format!(
+                    "/{}/{}",
+                    constants::ARTICLES_PATH,
+                    view.name.as_deref().unwrap_or_default()
+                );
+                let view = ArticleView {
+                    extensions: site.extensions,
+                    ..view
+                };
+                html! { <BlogDisplayerComponent url=url extra_args=view/> }
+            }
+            Some(Json(Err(_))) => view_invalid_response(),
+            None => spinner(),
+        }
+    }
+}
+
+// The profile of the author with the given id, rendered with the site's extensions
+pub struct AuthorPage;
+
+impl Displayer<Json<Result<SiteInfo, Error>>, String> for AuthorPage {
+    fn display(value: &Option<Json<Result<SiteInfo, Error>>>, id: String) -> Html {
+        match value {
+            Some(Json(Ok(site))) => html! {
+                <AuthorProfileComponent url={format!("{}/{}", constants::AUTHORS_URI, &id[..])} extra_args=site.extensions/>
+            },
+            Some(Json(Err(_))) => view_invalid_response(),
+            None => spinner(),
+        }
+    }
+}
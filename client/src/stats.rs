@@ -0,0 +1,144 @@
+use anyhow::Error;
+use http::{Request, Response};
+use shared::constants;
+use shared::stats::Stats;
+use std::collections::BTreeMap;
+use yew::format::{Json, Nothing};
+use yew::prelude::*;
+use yew::services::{fetch::FetchTask, FetchService};
+
+const STATS_DAYS: u64 = 30;
+
+pub struct StatsDashboard {
+    link: ComponentLink<Self>,
+    token: String,
+    fetch_task: Option<FetchTask>,
+    stats: Option<Result<Stats, String>>,
+}
+
+pub enum StatsMessage {
+    Token(String),
+    Load,
+    Loaded(Result<Stats, String>),
+}
+
+// Adds up the views of every entry sharing the same key, most viewed first
+fn totals<'a>(entries: impl Iterator<Item = (&'a str, u64)>) -> Vec<(&'a str, u64)> {
+    let mut totals = BTreeMap::new();
+    for (key, views) in entries {
+        *totals.entry(key).or_insert(0) += views;
+    }
+
+    let mut totals: Vec<_> = totals.into_iter().collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1));
+    totals
+}
+
+fn view_totals(title: &str, totals: Vec<(&str, u64)>) -> Html {
+    html! {
+        <table class="table table-sm table-dark stats-table">
+            <thead>
+                <tr>
+                    <th>{title}</th>
+                    <th>{"Views"}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {
+                    for totals.into_iter().map(|(key, views)| html! {
+                        <tr>
+                            <td>{key}</td>
+                            <td>{views}</td>
+                        </tr>
+                    })
+                }
+            </tbody>
+        </table>
+    }
+}
+
+fn view_stats(stats: &Stats) -> Html {
+    html! {
+        <>
+            <p>{format!("Since {}", stats.since)}</p>
+            {view_totals("Day", totals(stats.views.iter().map(|v| (v.date.as_str(), v.views))))}
+            {view_totals("Article", totals(stats.views.iter().map(|v| (v.article.as_str(), v.views))))}
+            {view_totals("Referrer", totals(stats.referrers.iter().map(|r| (r.referrer.as_str(), r.views))))}
+        </>
+    }
+}
+
+impl Component for StatsDashboard {
+    type Properties = ();
+    type Message = StatsMessage;
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        StatsDashboard {
+            link,
+            token: String::new(),
+            fetch_task: None,
+            stats: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            StatsMessage::Token(token) => self.token = token,
+            StatsMessage::Load => {
+                let get_req = Request::get(format!("{}?days={}", constants::STATS_URI, STATS_DAYS))
+                    .header("Authorization", format!("Bearer {}", self.token))
+                    .body(Nothing)
+                    .unwrap();
+                let callback =
+                    self.link
+                        .callback(|response: Response<Json<Result<Stats, Error>>>| {
+                            let status = response.status();
+                            StatsMessage::Loaded(if status.is_success() {
+                                response.into_body().0.map_err(|e| e.to_string())
+                            } else {
+                                Err(status.to_string())
+                            })
+                        });
+                self.fetch_task = Some(FetchService::fetch(get_req, callback).unwrap());
+            }
+            StatsMessage::Loaded(stats) => {
+                self.fetch_task = None;
+                self.stats = Some(stats);
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="bg-element-dark text-element-white stats">
+                <b style="font-weight: 1000;">{"Stats"}</b>
+                <input
+                    class="form-control"
+                    type="password"
+                    placeholder="Admin token"
+                    value=self.token.clone()
+                    oninput=self.link.callback(|e: InputData| StatsMessage::Token(e.value))
+                />
+                <button
+                    class="btn btn-outline-light"
+                    disabled=self.fetch_task.is_some()
+                    onclick=self.link.callback(|_| StatsMessage::Load)
+                >
+                    {"Load"}
+                </button>
+                {
+                    match &self.stats {
+                        Some(Ok(stats)) => view_stats(stats),
+                        Some(Err(e)) => html! {<p>{format!("Error: {}", e)}</p>},
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+}
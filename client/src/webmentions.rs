@@ -0,0 +1,43 @@
+use crate::date::format_date;
+use crate::errors::FetchError;
+use crate::request_loader::Displayer;
+use crate::request_loader::RequestLoader;
+use anyhow::Error;
+use shared::webmentions::Mentions;
+use yew::format::Json;
+use yew::prelude::*;
+
+pub type MentionsComponent = RequestLoader<MentionsDisplayer, Json<Result<Mentions, Error>>, ()>;
+
+pub struct MentionsDisplayer;
+
+impl Displayer<Json<Result<Mentions, Error>>, ()> for MentionsDisplayer {
+    fn display(value: &Option<Json<Result<Mentions, Error>>>, _: ()) -> Html {
+        match value {
+            Some(json) => match &json.0 {
+                Ok(mentions) if !mentions.mentions.is_empty() => html! {
+                    <div class="mentions text-element-white">
+                        <b style="font-weight: 1000;">{"Mentioned by"}</b>
+                        <ul>
+                            {
+                                for mentions.mentions.iter().map(|mention| html! {
+                                    <li>
+                                        <a href={mention.source.clone()} rel="nofollow">{&mention.source}</a>
+                                        {format!(" · {}", format_date(mention.verified_at))}
+                                    </li>
+                                })
+                            }
+                        </ul>
+                    </div>
+                },
+                // Most articles won't have mentions, so there's nothing to show otherwise
+                _ => html! {},
+            },
+            None => html! {},
+        }
+    }
+
+    fn display_error(_: &FetchError, _: Callback<MouseEvent>) -> Html {
+        html! {}
+    }
+}
@@ -0,0 +1,51 @@
+use crate::errors;
+use crate::sites::Sites;
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use std::future::{ready, Ready};
+
+// Extractor that only succeeds for requests carrying `Authorization: Bearer <token>`, the token
+// of the site the request is for(`admin_token`). Sites without a token have the administrative
+// endpoints(moderation, stats...) disabled altogether.
+pub struct Admin;
+
+// Takes as long wherever they differ, so the token can't be guessed a byte at a time by timing the
+// responses
+fn same_token(provided: &str, token: &str) -> bool {
+    provided.len() == token.len()
+        && provided
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn authorize(req: &HttpRequest) -> Result<Admin, actix_web::Error> {
+    let token = match req
+        .app_data::<web::Data<Sites>>()
+        .and_then(|sites| sites.for_request(req.head()).config.admin_token.clone())
+    {
+        Some(token) => token,
+        None => return Err(errors::forbidden("Administration is disabled")),
+    };
+
+    let provided = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(provided) if same_token(provided, &token) => Ok(Admin),
+        _ => Err(errors::unauthorized("Invalid admin token")),
+    }
+}
+
+impl FromRequest for Admin {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(authorize(req))
+    }
+}
@@ -0,0 +1,200 @@
+use crate::admin::Admin;
+use crate::clock;
+use crate::sites::Site;
+use crate::store::{Store, StoreResult};
+use actix_web::{dev::ServiceRequest, http::Method, web, HttpResponse, Result};
+use serde::Deserialize;
+use shared::constants;
+use shared::stats::{ReferrerCount, Stats, ViewCount};
+use url::Url;
+
+const VIEWS_TREE: &str = "views";
+const REFERRERS_TREE: &str = "referrers";
+const DEFAULT_STATS_DAYS: u64 = 30;
+const DIRECT_REFERRER: &str = "direct";
+const OTHER_REFERRER: &str = "other";
+// Longest a DNS name can be, the referrer is whatever the reader's browser(or anyone) sends
+const MAX_HOST_LENGTH: usize = 253;
+// Past this many different referrers in a day, new ones of the article are counted together so
+// made up ones can't fill the store
+const MAX_REFERRERS_PER_ARTICLE: usize = 50;
+
+// No cookies nor anything identifying the reader is kept, only aggregated counters
+pub struct PageView {
+    article: String,
+    referrer: String,
+}
+
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    days: Option<u64>,
+}
+
+// Only the host is kept from the referrer, and visits coming from the blog itself count as direct
+fn referrer_host(req: &ServiceRequest) -> String {
+    let headers = req.headers();
+    headers
+        .get(constants::PAGE_REFERRER_HEADER)
+        .or_else(|| headers.get("Referer"))
+        .and_then(|referrer| referrer.to_str().ok())
+        .and_then(|referrer| Url::parse(referrer).ok())
+        .and_then(|referrer| referrer.host_str().map(str::to_string))
+        .filter(|host| Some(host.as_str()) != req.connection_info().host().split(':').next())
+        .map(|host| {
+            if host.len() > MAX_HOST_LENGTH {
+                OTHER_REFERRER.to_string()
+            } else {
+                host
+            }
+        })
+        .unwrap_or_else(|| DIRECT_REFERRER.to_string())
+}
+
+// The client fetches the whole article only when it's displayed, so that's what counts as a view
+pub fn page_view(req: &ServiceRequest) -> Option<PageView> {
+    if req.method() != Method::GET {
+        return None;
+    }
+
+    let article = req
+        .path()
+        .strip_prefix(&format!("/{}/", constants::ARTICLES_PATH))?;
+    let article = percent_encoding::percent_decode_str(article)
+        .decode_utf8()
+        .ok()?;
+    if article.is_empty() || article.contains('/') {
+        return None;
+    }
+
+    Some(PageView {
+        article: article.into_owned(),
+        referrer: referrer_host(req),
+    })
+}
+
+pub fn record(store: &Store, view: &PageView) -> StoreResult<()> {
+    let day = clock::format_day(clock::now());
+    store.increment(VIEWS_TREE, &format!("{}/{}", day, view.article))?;
+
+    let prefix = format!("{}/{}/", day, view.article);
+    let key = format!("{}{}", prefix, view.referrer);
+    let known = store.get::<u64>(REFERRERS_TREE, &key)?.is_some()
+        || store.scan_prefix::<u64>(REFERRERS_TREE, &prefix)?.len() < MAX_REFERRERS_PER_ARTICLE;
+    if known {
+        store.increment(REFERRERS_TREE, &key)?;
+    } else {
+        store.increment(REFERRERS_TREE, &format!("{}{}", prefix, OTHER_REFERRER))?;
+    }
+    Ok(())
+}
+
+// Counter keys are `<day>/<article>[/<referrer>]`, returned split in their fields. Days sort
+// chronologically so only the keys from `since` on are read.
+fn counters(store: &Store, tree: &str, since: &str) -> StoreResult<Vec<(Vec<String>, u64)>> {
+    Ok(store
+        .scan_from::<u64>(tree, since)?
+        .into_iter()
+        .map(|(key, views)| (key.splitn(3, '/').map(str::to_string).collect(), views))
+        .collect())
+}
+
+pub async fn stats(_: Admin, site: Site, query: web::Query<StatsQuery>) -> Result<HttpResponse> {
+    let since = clock::format_day(clock::days_ago(query.days.unwrap_or(DEFAULT_STATS_DAYS)));
+
+    let views = counters(&site.store, VIEWS_TREE, &since)?
+        .into_iter()
+        .filter_map(|(fields, views)| match fields.as_slice() {
+            [date, article] => Some(ViewCount {
+                date: date.clone(),
+                article: article.clone(),
+                views,
+            }),
+            _ => None,
+        })
+        .collect();
+    let referrers = counters(&site.store, REFERRERS_TREE, &since)?
+        .into_iter()
+        .filter_map(|(fields, views)| match fields.as_slice() {
+            [date, article, referrer] => Some(ReferrerCount {
+                date: date.clone(),
+                article: article.clone(),
+                referrer: referrer.clone(),
+                views,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(Stats {
+        since,
+        views,
+        referrers,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn counters_start_at_the_first_day_in_range() {
+        let store = Store::temporary().unwrap();
+        for key in &["2020-12-31/a", "2021-01-01/a", "2021-01-02/b"] {
+            store.increment(VIEWS_TREE, key).unwrap();
+        }
+        let days: Vec<_> = counters(&store, VIEWS_TREE, "2021-01-01")
+            .unwrap()
+            .into_iter()
+            .map(|(fields, _)| fields.join("/"))
+            .collect();
+        assert_eq!(days, vec!["2021-01-01/a", "2021-01-02/b"]);
+
+        // Asking for more days than there are since the epoch gets everything
+        let since = clock::format_day(clock::days_ago(u64::MAX));
+        assert_eq!(since, "1970-01-01");
+        assert_eq!(counters(&store, VIEWS_TREE, &since).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn referrers_are_bounded() {
+        let long_host = format!("{}.example.com", "a".repeat(MAX_HOST_LENGTH));
+        for (referrer, host) in &[
+            ("https://example.com/post", "example.com"),
+            ("https://blog.test/", DIRECT_REFERRER),
+            ("not a url", DIRECT_REFERRER),
+            (&format!("https://{}/", long_host), OTHER_REFERRER),
+        ] {
+            let request = TestRequest::get()
+                .uri("/articles/a.md")
+                .header("Host", "blog.test")
+                .header(constants::PAGE_REFERRER_HEADER, *referrer)
+                .to_srv_request();
+            assert_eq!(referrer_host(&request), *host, "{}", referrer);
+        }
+
+        let store = Store::temporary().unwrap();
+        let view = |article: &str, referrer: String| PageView {
+            article: article.to_string(),
+            referrer,
+        };
+        for i in 0..MAX_REFERRERS_PER_ARTICLE + 10 {
+            record(&store, &view("a", format!("{}.example.com", i))).unwrap();
+        }
+        // Those already seen keep being counted on their own
+        record(&store, &view("a", String::from("0.example.com"))).unwrap();
+        record(&store, &view("b", String::from("new.example.com"))).unwrap();
+
+        let referrers = counters(&store, REFERRERS_TREE, "1970-01-01").unwrap();
+        let count = |article: &str, referrer: &str| {
+            referrers
+                .iter()
+                .find(|(fields, _)| fields[1] == article && fields[2] == referrer)
+                .map(|(_, views)| *views)
+        };
+        assert_eq!(referrers.len(), MAX_REFERRERS_PER_ARTICLE + 2);
+        assert_eq!(count("a", OTHER_REFERRER), Some(10));
+        assert_eq!(count("a", "0.example.com"), Some(2));
+        assert_eq!(count("b", "new.example.com"), Some(1));
+    }
+}
@@ -0,0 +1,794 @@
+use crate::authors::AuthorIndex;
+use crate::front_matter::{self, FrontMatter};
+use crate::shortcodes::ShortcodeRegistry;
+use crate::sites::Site;
+use crate::{clock, diagrams, errors, math};
+use actix_web::{web, HttpResponse, Result};
+use pulldown_cmark as pc;
+use serde::Deserialize;
+use shared::archive::{Archive, ArchiveMonth, ArchiveYear};
+use shared::article_list::{ArticleLink, ArticleMetadata, Articles, SortOrder};
+use shared::constants;
+use shared::markdown::{self, Extensions};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+
+const WORDS_PER_MINUTE: usize = 200;
+const ARTICLE_EXTENSION: &str = "md";
+const RELATED_ARTICLES: usize = 3;
+// Shorter words are mostly articles, pronouns and such, they say nothing about the topic
+const MIN_TERM_LENGTH: usize = 4;
+
+pub struct Article {
+    pub metadata: ArticleMetadata,
+    pub front_matter: FrontMatter,
+    // As written in the article's file
+    pub content: String,
+    // What's sent to the client, with everything that can be rendered ahead of time(shortcodes,
+    // math, diagrams...) already rendered
+    pub rendered: String,
+    // The beginning of `rendered` shown in the list of articles, see `ArticleIndex::set_previews`
+    pub preview: String,
+}
+
+// Every article is read once on startup and kept in memory, there aren't many of them. They're
+// kept in publication order.
+pub struct ArticleIndex {
+    articles: Vec<Article>,
+    // The site's, everything parsing the articles uses them
+    extensions: Extensions,
+}
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    #[serde(default)]
+    sort: SortOrder,
+}
+
+// Without leading zeros, so numbers compare by length first and then digit by digit
+fn take_number(chars: &mut Peekable<Chars>) -> String {
+    let mut number = String::new();
+    while let Some(digit) = chars.peek().copied().filter(char::is_ascii_digit) {
+        number.push(digit);
+        chars.next();
+    }
+    number.trim_start_matches('0').to_string()
+}
+
+// Compares the runs of digits by their value so that `2_...` goes before `10_...`
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, y) = (take_number(&mut a), take_number(&mut b));
+                match x.len().cmp(&y.len()).then_with(|| x.cmp(&y)) {
+                    Ordering::Equal => {}
+                    ordering => return ordering,
+                }
+            }
+            (Some(x), Some(y)) => match x.cmp(&y) {
+                Ordering::Equal => {
+                    a.next();
+                    b.next();
+                }
+                ordering => return ordering,
+            },
+        }
+    }
+}
+
+// By day, so that articles first seen by the same deploy don't get ordered by the second their
+// files were written. Within a day the `order` front matter key and then the name decide.
+fn publication_cmp(a: &Article, b: &Article) -> Ordering {
+    let day = |article: &Article| article.metadata.date.map(clock::day);
+    day(a)
+        .cmp(&day(b))
+        .then_with(|| a.order().cmp(&b.order()))
+        .then_with(|| natural_cmp(&a.metadata.name, &b.metadata.name))
+}
+
+// The text that's actually read: code blocks and image alt texts don't count
+fn prose(content: &str, extensions: Extensions) -> String {
+    let mut in_code_block = false;
+    let mut image_depth = 0;
+    let mut prose = String::new();
+    for event in markdown::parser(content, extensions) {
+        match event {
+            pc::Event::Start(pc::Tag::CodeBlock(_)) => in_code_block = true,
+            pc::Event::End(pc::Tag::CodeBlock(_)) => in_code_block = false,
+            pc::Event::Start(pc::Tag::Image(..)) => image_depth += 1,
+            pc::Event::End(pc::Tag::Image(..)) => image_depth -= 1,
+            pc::Event::Text(text) | pc::Event::Code(text) if !in_code_block && image_depth == 0 => {
+                prose.push_str(&text);
+                prose.push(' ');
+            }
+            _ => {}
+        }
+    }
+    prose
+}
+
+fn word_count(content: &str, extensions: Extensions) -> usize {
+    prose(content, extensions).split_whitespace().count()
+}
+
+fn reading_time(word_count: usize) -> u32 {
+    word_count.div_ceil(WORDS_PER_MINUTE).max(1) as u32
+}
+
+// The first top level heading, falling back to the file name
+fn title(name: &str, content: &str, extensions: Extensions) -> String {
+    let mut in_title = false;
+    let mut title = String::new();
+    for event in markdown::parser(content, extensions) {
+        match event {
+            pc::Event::Start(pc::Tag::Heading(1)) => in_title = true,
+            pc::Event::End(pc::Tag::Heading(1)) => break,
+            pc::Event::Text(text) | pc::Event::Code(text) if in_title => title += &text,
+            _ => {}
+        }
+    }
+
+    if title.is_empty() {
+        name.split('.').next().unwrap_or(name).replace("_", " ")
+    } else {
+        title
+    }
+}
+
+// Shortcodes go first so math and diagrams inside them are rendered too, and math leaves code
+// blocks alone so diagram sources aren't touched
+fn prerender(content: &str, shortcodes: &ShortcodeRegistry, extensions: Extensions) -> String {
+    let content = math::render_math(
+        &shortcodes.render_shortcodes(content, extensions),
+        extensions,
+    );
+    diagrams::render_diagrams(&content, extensions)
+}
+
+// Byte offsets where the top level blocks end. Raw HTML blocks come as one event per line so
+// they're left out, the block after them ends past them anyway.
+fn block_ends(content: &str, extensions: Extensions) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut depth = 0;
+    for (event, range) in markdown::parser(content, extensions).into_offset_iter() {
+        match event {
+            pc::Event::Start(_) => depth += 1,
+            pc::Event::End(_) => {
+                depth -= 1;
+                if depth == 0 {
+                    ends.push(range.end);
+                }
+            }
+            pc::Event::Rule if depth == 0 => ends.push(range.end),
+            _ => {}
+        }
+    }
+    ends
+}
+
+// The first `lines` lines of the article, up to the end of the block they stop in so that math,
+// diagrams and shortcodes are never cut halfway
+fn preview_source<'a>(
+    body: &'a str,
+    lines: usize,
+    shortcodes: &ShortcodeRegistry,
+    extensions: Extensions,
+) -> &'a str {
+    block_ends(body, extensions)
+        .into_iter()
+        .find(|&end| {
+            body[..end].lines().count() >= lines
+                && !shortcodes.inside_shortcode(body, end, extensions)
+        })
+        .map_or(body, |end| &body[..end])
+}
+
+// `#articles/<name>` and `#articles/<name>/<heading>` links, returning the decoded name and the
+// heading if any
+pub fn article_route(target: &str) -> Option<(String, Option<&str>)> {
+    let route = target.strip_prefix(&format!("#{}/", constants::ARTICLES_PATH))?;
+    // Article names don't have slashes, what follows one is the heading
+    let mut parts = route.splitn(2, '/');
+    let name = percent_encoding::percent_decode_str(parts.next()?)
+        .decode_utf8_lossy()
+        .into_owned();
+    Some((name, parts.next()))
+}
+
+fn linked_articles(content: &str, extensions: Extensions) -> HashSet<String> {
+    markdown::parser(content, extensions)
+        .filter_map(|event| match event {
+            pc::Event::Start(pc::Tag::Link(_, target, _)) => {
+                article_route(&target).map(|(name, _)| name)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Distinctive words of every article, the ones used in at most half of them. Words every article
+// uses would make them all related.
+fn terms(articles: &[Article], extensions: Extensions) -> Vec<HashSet<String>> {
+    let words: Vec<HashSet<String>> = articles
+        .iter()
+        .map(|article| {
+            prose(article.body(), extensions)
+                .split_whitespace()
+                .map(|word| {
+                    word.trim_matches(|c: char| !c.is_alphanumeric())
+                        .to_lowercase()
+                })
+                .filter(|word| word.chars().count() >= MIN_TERM_LENGTH)
+                .collect()
+        })
+        .collect();
+
+    let mut frequency: HashMap<&str, usize> = HashMap::new();
+    for word in words.iter().flatten() {
+        *frequency.entry(word).or_insert(0) += 1;
+    }
+
+    let max_frequency = (articles.len() / 2).max(1);
+    words
+        .iter()
+        .map(|words| {
+            words
+                .iter()
+                .filter(|word| frequency[word.as_str()] <= max_frequency)
+                .cloned()
+                .collect()
+        })
+        .collect()
+}
+
+// Every shared tag counts as much as having exactly the same terms
+fn similarity(a: (&Article, &HashSet<String>), b: (&Article, &HashSet<String>)) -> f64 {
+    let shared_tags =
+        a.0.metadata
+            .tags
+            .iter()
+            .filter(|tag| b.0.metadata.tags.contains(tag))
+            .count();
+    let all_terms = a.1.union(b.1).count();
+    let shared_terms = if all_terms == 0 {
+        0.0
+    } else {
+        a.1.intersection(b.1).count() as f64 / all_terms as f64
+    };
+    shared_tags as f64 + shared_terms
+}
+
+fn article_link(article: &Article) -> ArticleLink {
+    ArticleLink {
+        name: article.metadata.name.clone(),
+        title: article.metadata.title.clone(),
+    }
+}
+
+// Fills the backlinks and related articles of every article, once they're all loaded
+fn link_articles(articles: &mut [Article], extensions: Extensions) {
+    let links: Vec<HashSet<String>> = articles
+        .iter()
+        .map(|article| linked_articles(article.body(), extensions))
+        .collect();
+    let terms = terms(articles, extensions);
+
+    let mut backlinks = Vec::with_capacity(articles.len());
+    let mut related = Vec::with_capacity(articles.len());
+    for (i, article) in articles.iter().enumerate() {
+        let name = &article.metadata.name;
+        backlinks.push(
+            articles
+                .iter()
+                .zip(&links)
+                .filter(|(other, links)| other.metadata.name != *name && links.contains(name))
+                .map(|(other, _)| article_link(other))
+                .collect::<Vec<_>>(),
+        );
+
+        let mut scores: Vec<(f64, &Article)> = articles
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(j, other)| (similarity((article, &terms[i]), (other, &terms[j])), other))
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+        scores.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+        related.push(
+            scores
+                .into_iter()
+                .take(RELATED_ARTICLES)
+                .map(|(_, other)| article_link(other))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    for ((article, backlinks), related) in articles.iter_mut().zip(backlinks).zip(related) {
+        article.metadata.backlinks = backlinks;
+        article.metadata.related = related;
+    }
+}
+
+fn link_neighbours(articles: &mut [Article]) {
+    let links: Vec<ArticleLink> = articles.iter().map(article_link).collect();
+    for (i, article) in articles.iter_mut().enumerate() {
+        article.metadata.previous = i.checked_sub(1).map(|previous| links[previous].clone());
+        article.metadata.next = links.get(i + 1).cloned();
+    }
+}
+
+impl Article {
+    fn new(
+        name: String,
+        content: String,
+        shortcodes: &ShortcodeRegistry,
+        extensions: Extensions,
+    ) -> Article {
+        let (front_matter, body) = front_matter::split(&content);
+        let word_count = word_count(body, extensions);
+        let title = match front_matter.get("title") {
+            Some(title) => title.to_string(),
+            None => title(&name, body, extensions),
+        };
+        let date = front_matter.get("date").and_then(|date| {
+            let parsed = clock::parse_day(date);
+            if parsed.is_none() {
+                println!("Invalid date `{}` in {}, expected YYYY-MM-DD", date, name);
+            }
+            parsed
+        });
+        Article {
+            metadata: ArticleMetadata {
+                title,
+                reading_time: reading_time(word_count),
+                word_count,
+                date,
+                tags: front_matter.list("tags"),
+                authors: Vec::new(),
+                backlinks: Vec::new(),
+                related: Vec::new(),
+                previous: None,
+                next: None,
+                name,
+            },
+            rendered: prerender(body, shortcodes, extensions),
+            preview: String::new(),
+            front_matter,
+            content,
+        }
+    }
+
+    // The article without its front matter
+    pub fn body(&self) -> &str {
+        front_matter::split(&self.content).1
+    }
+
+    // Orders articles published the same day, e.g. the parts of a series
+    fn order(&self) -> Option<i64> {
+        self.front_matter.get("order")?.parse().ok()
+    }
+}
+
+impl ArticleIndex {
+    pub fn load(dir: &Path, extensions: Extensions) -> io::Result<ArticleIndex> {
+        let shortcodes = ShortcodeRegistry::default();
+        let mut articles = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir()
+                || entry.path().extension() != Some(ARTICLE_EXTENSION.as_ref())
+            {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let content = fs::read_to_string(entry.path())?;
+            articles.push(Article::new(name, content, &shortcodes, extensions));
+        }
+
+        articles.sort_by(publication_cmp);
+        link_neighbours(&mut articles);
+        link_articles(&mut articles, extensions);
+        Ok(ArticleIndex {
+            articles,
+            extensions,
+        })
+    }
+
+    pub fn extensions(&self) -> Extensions {
+        self.extensions
+    }
+
+    // Articles without a `date` in their front matter were published when their first revision
+    // was recorded
+    pub fn set_publication_dates(&mut self, first_revisions: &HashMap<String, u64>) {
+        for article in &mut self.articles {
+            if article.metadata.date.is_none() {
+                article.metadata.date = first_revisions.get(&article.metadata.name).copied();
+            }
+        }
+        self.articles.sort_by(publication_cmp);
+        link_neighbours(&mut self.articles);
+    }
+
+    // Previews are rendered from the beginning of the source rather than cut from the rendered
+    // article, which has HTML(MathML, SVG...) spanning several lines
+    pub fn set_previews(&mut self, lines: usize) {
+        let shortcodes = ShortcodeRegistry::default();
+        for article in &mut self.articles {
+            let body = front_matter::split(&article.content).1;
+            let source = preview_source(body, lines, &shortcodes, self.extensions);
+            article.preview = if source.len() == body.len() {
+                article.rendered.clone()
+            } else {
+                prerender(source, &shortcodes, self.extensions)
+            };
+        }
+    }
+
+    // Articles without `authors` in their front matter are by the site's only author, if it has
+    // just one
+    pub fn set_authors(&mut self, authors: &AuthorIndex) {
+        for article in &mut self.articles {
+            let mut ids = article.front_matter.list("authors");
+            if let ([], [author]) = (&ids[..], authors.authors()) {
+                ids.push(author.id.clone());
+            }
+            article.metadata.authors = ids
+                .iter()
+                .filter_map(|id| {
+                    let link = authors.link(id);
+                    if link.is_none() {
+                        println!("Unknown author `{}` in {}", id, article.metadata.name);
+                    }
+                    link
+                })
+                .collect();
+        }
+    }
+
+    pub fn sorted(&self, order: SortOrder) -> Vec<&Article> {
+        let mut articles: Vec<&Article> = self.articles.iter().collect();
+        match order {
+            SortOrder::Oldest => {}
+            SortOrder::Newest => articles.reverse(),
+            SortOrder::Title => articles.sort_by(|a, b| {
+                natural_cmp(
+                    &a.metadata.title.to_lowercase(),
+                    &b.metadata.title.to_lowercase(),
+                )
+            }),
+        }
+        articles
+    }
+
+    pub fn articles(&self) -> impl Iterator<Item = &Article> {
+        self.articles.iter()
+    }
+
+    // Articles without a publication date are left out
+    pub fn archive(&self) -> Archive {
+        let mut years: Vec<ArchiveYear> = Vec::new();
+        for article in self.sorted(SortOrder::Newest) {
+            let date = match article.metadata.date {
+                Some(date) => date,
+                None => continue,
+            };
+            let (year, month, _) = clock::civil_date(date);
+            let (year, month) = (year as u32, month as u32);
+
+            if years.last().map(|y| y.year) != Some(year) {
+                years.push(ArchiveYear {
+                    year,
+                    count: 0,
+                    months: Vec::new(),
+                });
+            }
+            if let Some(archive_year) = years.last_mut() {
+                archive_year.count += 1;
+                if archive_year.months.last().map(|m| m.month) != Some(month) {
+                    archive_year.months.push(ArchiveMonth {
+                        month,
+                        articles: Vec::new(),
+                    });
+                }
+                if let Some(archive_month) = archive_year.months.last_mut() {
+                    archive_month.articles.push(article_link(article));
+                }
+            }
+        }
+        Archive { years }
+    }
+
+    // What requests go through to get to an article: only the plain file name of an article in the
+    // index is accepted, so nothing(`..`, absolute paths, other files) can point anywhere else
+    pub fn resolve(&self, slug: &str) -> Option<&Article> {
+        let path = Path::new(slug);
+        let is_file_name = path.file_name() == Some(slug.as_ref()) && !slug.contains('\\');
+        if !is_file_name || path.extension() != Some(ARTICLE_EXTENSION.as_ref()) {
+            return None;
+        }
+        self.get(slug)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Article> {
+        self.articles.iter().find(|a| a.metadata.name == name)
+    }
+}
+
+pub async fn list(site: Site, query: web::Query<ListQuery>) -> HttpResponse {
+    HttpResponse::Ok().json(Articles {
+        articles: site
+            .index
+            .sorted(query.sort)
+            .into_iter()
+            .map(|a| a.metadata.clone())
+            .collect(),
+        page_size: site.config.page_size,
+        extensions: site.config.extensions,
+    })
+}
+
+pub async fn archive(site: Site) -> HttpResponse {
+    HttpResponse::Ok().json(site.index.archive())
+}
+
+pub async fn metadata(site: Site, name: web::Path<String>) -> Result<HttpResponse> {
+    match site.index.resolve(&name) {
+        Some(article) => Ok(HttpResponse::Ok().json(&article.metadata)),
+        None => Err(errors::not_found("Unknown article")),
+    }
+}
+
+pub async fn content(site: Site, name: web::Path<String>) -> Result<HttpResponse> {
+    match site.index.resolve(&name) {
+        Some(article) => Ok(HttpResponse::Ok()
+            .content_type("text/markdown; charset=utf-8")
+            .body(article.rendered.clone())),
+        None => Err(errors::not_found("Unknown article")),
+    }
+}
+
+// The beginning of the article, for the list of articles
+pub async fn preview(site: Site, name: web::Path<String>) -> Result<HttpResponse> {
+    match site.index.resolve(&name) {
+        Some(article) => Ok(HttpResponse::Ok()
+            .content_type("text/markdown; charset=utf-8")
+            .body(article.preview.clone())),
+        None => Err(errors::not_found("Unknown article")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{SiteConfig, MAIN_SITE_ID};
+    use crate::sites::{SiteState, Sites};
+    use crate::store::Store;
+    use crate::temp_dir::TempDir;
+    use actix_web::{test, App};
+
+    // An articles directory next to a file that must never be served
+    fn fixture() -> TempDir {
+        TempDir::with_files(&[
+            ("articles/1_first.md", "# First\n\nHello"),
+            ("articles/notes.txt", "Not an article"),
+            ("articles/img/hidden.md", "# Hidden"),
+            ("secret.md", "# Secret"),
+        ])
+    }
+
+    fn article(name: &str, content: &str) -> Article {
+        Article::new(
+            name.to_string(),
+            content.to_string(),
+            &ShortcodeRegistry::default(),
+            Extensions::default(),
+        )
+    }
+
+    fn index(mut articles: Vec<Article>) -> ArticleIndex {
+        articles.sort_by(publication_cmp);
+        ArticleIndex {
+            articles,
+            extensions: Extensions::default(),
+        }
+    }
+
+    #[test]
+    fn numbers_compare_by_their_value() {
+        for (a, b, ordering) in &[
+            ("2_x.md", "10_x.md", Ordering::Less),
+            ("10_x.md", "2_x.md", Ordering::Greater),
+            ("10_x.md", "10_x.md", Ordering::Equal),
+            ("02_x.md", "2_x.md", Ordering::Equal),
+            ("x_9.md", "x_10.md", Ordering::Less),
+            ("1_a.md", "1_b.md", Ordering::Less),
+            ("a.md", "a_1.md", Ordering::Less),
+            ("part", "part_2", Ordering::Less),
+        ] {
+            assert_eq!(natural_cmp(a, b), *ordering, "{} and {}", a, b);
+        }
+    }
+
+    #[test]
+    fn articles_are_ordered_by_day_then_order_then_name() {
+        let mut same_deploy = article("2_later.md", "");
+        same_deploy.metadata.date = clock::parse_day("2020-01-01").map(|day| day + 3600);
+        let mut seen_first = article("10_earlier.md", "");
+        seen_first.metadata.date = clock::parse_day("2020-01-01").map(|day| day + 60);
+
+        for (a, b, ordering) in &[
+            (
+                article("10_x.md", "---\ndate: 2020-01-01\n---\n"),
+                article("2_x.md", "---\ndate: 2020-01-02\n---\n"),
+                Ordering::Less,
+            ),
+            (
+                article("2_x.md", "---\ndate: 2020-01-01\n---\n"),
+                article("10_x.md", "---\ndate: 2020-01-01\n---\n"),
+                Ordering::Less,
+            ),
+            (
+                article("a.md", "---\ndate: 2020-01-01\norder: 2\n---\n"),
+                article("b.md", "---\ndate: 2020-01-01\norder: 1\n---\n"),
+                Ordering::Greater,
+            ),
+            (
+                article("a.md", "---\ndate: 2020-01-01\n---\n"),
+                article("a.md", "---\ndate: 2020-01-01\n---\n"),
+                Ordering::Equal,
+            ),
+            // The time of the day doesn't matter
+            (same_deploy, seen_first, Ordering::Less),
+        ] {
+            assert_eq!(
+                publication_cmp(a, b),
+                *ordering,
+                "{} and {}",
+                a.metadata.name,
+                b.metadata.name
+            );
+        }
+    }
+
+    #[test]
+    fn only_prose_is_read() {
+        for (content, words) in &[
+            ("", 0),
+            ("One two three", 3),
+            (
+                "---\ntags: a, b\n---\n# Title\n\nSome *emphasized* words",
+                4,
+            ),
+            ("Text\n\n```rust\nlet code = 1;\n```\n", 1),
+            ("Text\n\n    indented code\n", 1),
+            ("![an alt text](img.png) Caption", 1),
+            ("Inline `code` counts", 3),
+        ] {
+            assert_eq!(
+                article("a.md", content).metadata.word_count,
+                *words,
+                "{}",
+                content
+            );
+        }
+
+        for (word_count, minutes) in &[(0, 1), (1, 1), (200, 1), (201, 2), (400, 2), (401, 3)] {
+            assert_eq!(reading_time(*word_count), *minutes, "{} words", word_count);
+        }
+    }
+
+    #[test]
+    fn previews_stop_at_the_end_of_a_block() {
+        let body = "# Title\n\nFirst paragraph\nstill the first\n\n$$\nx^2\n$$\n\n\
+                    {{< note >}}\n\nInside\n\n{{< /note >}}\n\nLast\n";
+        let shortcodes = ShortcodeRegistry::default();
+        for (lines, cut_at) in &[
+            (1, 1),
+            (3, 4),
+            (6, 8),
+            (10, 14),
+            (12, 14),
+            (16, 16),
+            (100, 16),
+        ] {
+            let preview = preview_source(body, *lines, &shortcodes, Extensions::default());
+            assert_eq!(preview.lines().count(), *cut_at, "{} lines", lines);
+            assert!(body.starts_with(preview));
+        }
+    }
+
+    #[test]
+    fn archive_groups_dated_articles_newest_first() {
+        let dated = |name: &str, date: &str| article(name, &format!("---\ndate: {}\n---\n", date));
+        let archive = index(vec![
+            dated("a.md", "2020-01-05"),
+            dated("b.md", "2020-01-20"),
+            dated("c.md", "2020-03-01"),
+            dated("d.md", "2021-02-01"),
+            article("undated.md", ""),
+        ])
+        .archive();
+
+        let years: Vec<_> = archive
+            .years
+            .iter()
+            .map(|year| {
+                let months: Vec<_> = year
+                    .months
+                    .iter()
+                    .map(|month| {
+                        let names: Vec<_> =
+                            month.articles.iter().map(|a| a.name.as_str()).collect();
+                        (month.month, names)
+                    })
+                    .collect();
+                (year.year, year.count, months)
+            })
+            .collect();
+        assert_eq!(
+            years,
+            vec![
+                (2021, 1, vec![(2, vec!["d.md"])]),
+                (2020, 3, vec![(3, vec!["c.md"]), (1, vec!["b.md", "a.md"])]),
+            ]
+        );
+        assert!(index(vec![article("undated.md", "")])
+            .archive()
+            .years
+            .is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn only_articles_in_the_index_are_served() {
+        let fixture = fixture();
+        let config = SiteConfig::for_tests(MAIN_SITE_ID, &[], fixture.join("articles"));
+        let (site, _) = SiteState::load(config, &Store::temporary().unwrap()).unwrap();
+        let mut app = test::init_service(
+            App::new()
+                .data(Sites::new(site, Vec::new()))
+                .route("/preview/articles/{name:.*}", web::get().to(preview))
+                .route("/articles/{name}", web::get().to(content)),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/preview/articles/1_first.md")
+            .to_request();
+        assert!(test::call_service(&mut app, request)
+            .await
+            .status()
+            .is_success());
+
+        for uri in &[
+            "/preview/articles/../secret.md",
+            "/preview/articles/..%2Fsecret.md",
+            "/preview/articles/%2E%2E/secret.md",
+            "/preview/articles//etc/passwd",
+            "/preview/articles/img/hidden.md",
+            "/preview/articles/img",
+            "/preview/articles/notes.txt",
+            "/preview/articles/..",
+            "/preview/articles/",
+            "/preview/articles/unknown.md",
+            "/articles/..%2Fsecret.md",
+            "/articles/notes.txt",
+        ] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+            let status = test::call_service(&mut app, request).await.status();
+            assert_eq!(status, 404, "{} answered {}", uri, status);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::errors;
+use crate::front_matter;
+use crate::sites::Site;
+use actix_web::{web, HttpResponse, Result};
+use shared::authors::{Author, AuthorLink, Authors, ProfileLink};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// One markdown file per author, named after their id, with their bio after the front matter:
+//
+//     ---
+//     name: Jane Doe
+//     avatar: ./imgs/jane.png
+//     location: Somewhere
+//     links: Mail mailto:jane@example.com, GitHub https://github.com/jane
+//     ---
+//     What Jane writes about.
+//
+// Every link is a label followed by its URL.
+
+const AUTHOR_EXTENSION: &str = "md";
+
+#[derive(Default)]
+pub struct AuthorIndex {
+    // Sorted by name
+    authors: Vec<Author>,
+}
+
+fn profile_link(link: &str) -> ProfileLink {
+    match link.rfind(char::is_whitespace) {
+        Some(end) => ProfileLink {
+            label: link[..end].trim().to_string(),
+            url: link[end..].trim().to_string(),
+        },
+        None => ProfileLink {
+            label: link.to_string(),
+            url: link.to_string(),
+        },
+    }
+}
+
+fn parse_author(id: String, content: &str) -> Author {
+    let (front_matter, bio) = front_matter::split(content);
+    let get = |key| front_matter.get(key).map(str::to_string);
+    Author {
+        name: get("name").unwrap_or_else(|| id.clone()),
+        avatar: get("avatar"),
+        location: get("location"),
+        bio: bio.trim().to_string(),
+        links: front_matter
+            .list("links")
+            .iter()
+            .map(|link| profile_link(link))
+            .collect(),
+        id,
+    }
+}
+
+impl AuthorIndex {
+    pub fn load(dir: &Path) -> io::Result<AuthorIndex> {
+        let mut authors = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() || path.extension() != Some(AUTHOR_EXTENSION.as_ref()) {
+                continue;
+            }
+
+            let id = match path.file_stem() {
+                Some(stem) => stem.to_string_lossy().into_owned(),
+                None => continue,
+            };
+            authors.push(parse_author(id, &fs::read_to_string(&path)?));
+        }
+
+        authors.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(AuthorIndex { authors })
+    }
+
+    pub fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Author> {
+        self.authors.iter().find(|author| author.id == id)
+    }
+
+    pub fn link(&self, id: &str) -> Option<AuthorLink> {
+        self.get(id).map(|author| AuthorLink {
+            id: author.id.clone(),
+            name: author.name.clone(),
+        })
+    }
+}
+
+pub async fn list(site: Site) -> HttpResponse {
+    HttpResponse::Ok().json(Authors {
+        authors: site.authors.authors().to_vec(),
+    })
+}
+
+pub async fn author(site: Site, id: web::Path<String>) -> Result<HttpResponse> {
+    match site.authors.get(&id) {
+        Some(author) => Ok(HttpResponse::Ok().json(author)),
+        None => Err(errors::not_found("Unknown author")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::articles::ArticleIndex;
+    use crate::temp_dir::TempDir;
+    use shared::markdown::Extensions;
+
+    #[test]
+    fn articles_link_to_the_authors_in_their_front_matter() {
+        let authors_dir = TempDir::with_files(&[
+            (
+                "jane.md",
+                "---\nname: Jane Doe\nlinks: Regarding this blog mailto:jane@example.com, https://example.com\n---\nWrites things.\n",
+            ),
+            ("john.md", "Also writes things."),
+            ("notes.txt", "Not an author"),
+        ]);
+        let authors = AuthorIndex::load(authors_dir.path()).unwrap();
+
+        let ids: Vec<_> = authors.authors().iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["jane", "john"]);
+        let jane = authors.get("jane").unwrap();
+        assert_eq!(jane.bio, "Writes things.");
+        assert_eq!(
+            jane.links,
+            vec![
+                ProfileLink {
+                    label: String::from("Regarding this blog"),
+                    url: String::from("mailto:jane@example.com"),
+                },
+                ProfileLink {
+                    label: String::from("https://example.com"),
+                    url: String::from("https://example.com"),
+                },
+            ]
+        );
+        assert_eq!(authors.get("john").unwrap().name, "john");
+
+        let articles_dir = TempDir::with_files(&[
+            ("both.md", "---\nauthors: john, jane, nobody\n---\n# Both"),
+            ("none.md", "# None"),
+        ]);
+        let mut index = ArticleIndex::load(articles_dir.path(), Extensions::default()).unwrap();
+        index.set_authors(&authors);
+        let names = |article: &str| -> Vec<String> {
+            let article = index.resolve(article).unwrap();
+            article
+                .metadata
+                .authors
+                .iter()
+                .map(|a| a.name.clone())
+                .collect()
+        };
+        assert_eq!(names("both.md"), vec!["john", "Jane Doe"]);
+        assert!(names("none.md").is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::admin::Admin;
+use crate::clock;
+use crate::errors;
+use crate::rate_limit::{ClientAddress, RateLimiter};
+use crate::sites::Site;
+use crate::store::{Store, StoreResult};
+use actix_web::{web, HttpResponse, Result};
+use shared::comments::{
+    Comment, CommentStatus, CommentThread, Comments, ModerationAction, ModerationDecision,
+    NewComment,
+};
+use shared::constants;
+
+const COMMENTS_TREE: &str = "comments";
+
+// Zero padded so comments are listed in posting order
+fn comment_key(article: &str, id: u64) -> String {
+    format!("{}/{:020}", article, id)
+}
+
+fn article_comments(store: &Store, article: &str) -> StoreResult<Vec<Comment>> {
+    Ok(store
+        .scan_prefix(COMMENTS_TREE, &format!("{}/", article))?
+        .into_iter()
+        .map(|(_, comment)| comment)
+        .collect())
+}
+
+fn build_threads(comments: &[Comment], parent: Option<u64>) -> Vec<CommentThread> {
+    comments
+        .iter()
+        .filter(|comment| comment.parent == parent)
+        .map(|comment| CommentThread {
+            comment: comment.clone(),
+            replies: build_threads(comments, Some(comment.id)),
+        })
+        .collect()
+}
+
+fn validate(new_comment: &NewComment) -> Result<()> {
+    let author = new_comment.author.trim();
+    let body = new_comment.body.trim();
+    if author.is_empty() || author.chars().count() > constants::MAX_COMMENT_AUTHOR_LENGTH {
+        return Err(errors::bad_request(format!(
+            "The name must have between 1 and {} characters",
+            constants::MAX_COMMENT_AUTHOR_LENGTH
+        )));
+    }
+
+    if body.is_empty() || body.chars().count() > constants::MAX_COMMENT_LENGTH {
+        return Err(errors::bad_request(format!(
+            "The comment must have between 1 and {} characters",
+            constants::MAX_COMMENT_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+fn known_article(site: &Site, article: &str) -> Result<String> {
+    match site.index.resolve(article) {
+        Some(article) => Ok(article.metadata.name.clone()),
+        None => Err(errors::not_found("Unknown article")),
+    }
+}
+
+// Only approved comments are public, so only those are returned, already threaded
+pub async fn list(site: Site, article: web::Path<String>) -> Result<HttpResponse> {
+    let article = known_article(&site, &article)?;
+    let approved: Vec<_> = article_comments(&site.store, &article)?
+        .into_iter()
+        .filter(|comment| comment.status == CommentStatus::Approved)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(Comments {
+        threads: build_threads(&approved, None),
+        article,
+    }))
+}
+
+// New comments go to the moderation queue and aren't listed until approved
+pub async fn post(
+    client: ClientAddress,
+    site: Site,
+    rate_limiter: web::Data<RateLimiter>,
+    article: web::Path<String>,
+    new_comment: web::Json<NewComment>,
+) -> Result<HttpResponse> {
+    if !rate_limiter.check(&client.0) {
+        return Err(errors::too_many_requests(
+            "Too many comments, try again later",
+        ));
+    }
+
+    let store = &site.store;
+    let article = known_article(&site, &article)?;
+    let new_comment = new_comment.into_inner();
+    validate(&new_comment)?;
+
+    if let Some(parent) = new_comment.parent {
+        let parent: Option<Comment> = store.get(COMMENTS_TREE, &comment_key(&article, parent))?;
+        if parent.map(|p| p.status) != Some(CommentStatus::Approved) {
+            return Err(errors::bad_request("Replying to an unknown comment"));
+        }
+    }
+
+    let comment = Comment {
+        id: store.generate_id()?,
+        article,
+        parent: new_comment.parent,
+        author: new_comment.author.trim().to_string(),
+        body: new_comment.body.trim().to_string(),
+        timestamp: clock::now(),
+        status: CommentStatus::Pending,
+    };
+    store.insert(
+        COMMENTS_TREE,
+        &comment_key(&comment.article, comment.id),
+        &comment,
+    )?;
+
+    Ok(HttpResponse::Accepted().json(comment))
+}
+
+pub async fn moderation_queue(_: Admin, site: Site) -> Result<HttpResponse> {
+    let pending: Vec<Comment> = site
+        .store
+        .scan_prefix(COMMENTS_TREE, "")?
+        .into_iter()
+        .map(|(_, comment)| comment)
+        .filter(|comment: &Comment| comment.status == CommentStatus::Pending)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(pending))
+}
+
+pub async fn moderate(
+    _: Admin,
+    site: Site,
+    path: web::Path<(String, u64)>,
+    decision: web::Json<ModerationDecision>,
+) -> Result<HttpResponse> {
+    let (article, id) = path.into_inner();
+    let key = comment_key(&article, id);
+    let mut comment: Comment = match site.store.get(COMMENTS_TREE, &key)? {
+        Some(comment) => comment,
+        None => return Err(errors::not_found("Unknown comment")),
+    };
+
+    comment.status = match decision.action {
+        ModerationAction::Approve => CommentStatus::Approved,
+        ModerationAction::Reject => CommentStatus::Rejected,
+    };
+    site.store.insert(COMMENTS_TREE, &key, &comment)?;
+
+    Ok(HttpResponse::Ok().json(comment))
+}
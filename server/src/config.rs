@@ -0,0 +1,857 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde::Deserialize;
+use shared::constants;
+use shared::markdown::Extensions;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use url::Url;
+
+// Settings come in layers, each one overriding the previous: the defaults, the TOML file(`--config`,
+// `BLOG_CONFIG` or `blog.toml` if it exists), environment variables and command line flags.
+// Everything is validated once all of them are applied, so mistakes show up on startup.
+//
+// The top level settings are those of the main site, answering for any host. Other sites are
+// `[[sites]]` tables of the file, each one with its own hosts, articles and URL. They take the
+// main site's settings that are about how the blog looks and works(see `SiteSettings::inherited`)
+// but never what belongs to it, like its admin token or about page.
+
+const DEFAULT_CONFIG_FILE: &str = "blog.toml";
+const DEFAULT_PORT: u16 = 8080;
+const DEFAULT_STORE_PATH: &str = "./data";
+const DEFAULT_PAGE_SIZE: usize = 5;
+const DEFAULT_PREVIEW_LINES: usize = 10;
+const DEFAULT_SITE_TITLE: &str = "Taping Memory";
+const DEFAULT_SITE_URL: &str = "https://taping-memory.xyz/";
+const DEFAULT_REVISION_AUTHOR: &str = "conectado";
+const DEFAULT_PLAYGROUND_URL: &str = "https://play.rust-lang.org/execute";
+const AUTHORS_PATH: &str = "authors";
+const FEATURES: [&str; 4] = ["comments", "webmentions", "playground", "analytics"];
+pub const MAIN_SITE_ID: &str = "default";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Features {
+    pub comments: bool,
+    pub webmentions: bool,
+    pub playground: bool,
+    // Page views and referrers, see `analytics`
+    pub analytics: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SiteConfig {
+    // Names the site in the logs and keeps its data apart in the store
+    pub id: String,
+    // Lowercase and without port, empty for the main site
+    pub hosts: Vec<String>,
+    pub articles_dir: PathBuf,
+    // One markdown file per author, `<articles_dir>/authors` if there's one
+    pub authors_dir: Option<PathBuf>,
+    // Articles per page in the list
+    pub page_size: usize,
+    // Lines of every article shown in the list
+    pub preview_lines: usize,
+    pub site_title: String,
+    pub site_url: Url,
+    // Every revision is credited to them, see `revisions::record_revisions`
+    pub revision_author: String,
+    // The administrative endpoints are disabled without one
+    pub admin_token: Option<String>,
+    // Stylesheet applied over the client's one
+    pub theme: Option<PathBuf>,
+    // Markdown shown as the about page instead of the client's one
+    pub about: Option<PathBuf>,
+    pub features: Features,
+    // Markdown extensions the articles are written with, the client renders them with the same
+    pub extensions: Extensions,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind_address: IpAddr,
+    pub port: u16,
+    // Served as is for every site, the client's build output
+    pub static_dir: PathBuf,
+    pub store_path: PathBuf,
+    pub playground_url: Url,
+    // Whether the server is only reached through a reverse proxy appending the client's address
+    // to `X-Forwarded-For`, like Heroku's router
+    pub behind_proxy: bool,
+    // Answers the requests for hosts no other site has
+    pub main_site: SiteConfig,
+    pub sites: Vec<SiteConfig>,
+    // `check-links` reports the broken links of the articles instead of serving them
+    pub check_links: bool,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    // The setting, where it came from and what's wrong with it
+    Invalid(&'static str, String, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "couldn't read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid {}: {}", path.display(), e),
+            ConfigError::Invalid(setting, source, reason) => {
+                write!(f, "invalid `{}`({}): {}", setting, source, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+struct FeaturesLayer {
+    comments: Option<bool>,
+    webmentions: Option<bool>,
+    playground: Option<bool>,
+    analytics: Option<bool>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+struct ExtensionsLayer {
+    tables: Option<bool>,
+    footnotes: Option<bool>,
+    strikethrough: Option<bool>,
+    task_lists: Option<bool>,
+    smart_punctuation: Option<bool>,
+    heading_attributes: Option<bool>,
+}
+
+// What a single source sets, as written in the TOML file
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Layer {
+    bind_address: Option<String>,
+    port: Option<u16>,
+    static_dir: Option<PathBuf>,
+    articles_dir: Option<PathBuf>,
+    authors_dir: Option<PathBuf>,
+    store_path: Option<PathBuf>,
+    page_size: Option<usize>,
+    preview_lines: Option<usize>,
+    site_title: Option<String>,
+    site_url: Option<String>,
+    revision_author: Option<String>,
+    admin_token: Option<String>,
+    playground_url: Option<String>,
+    behind_proxy: Option<bool>,
+    theme: Option<PathBuf>,
+    about: Option<PathBuf>,
+    features: FeaturesLayer,
+    extensions: ExtensionsLayer,
+    sites: Vec<SiteLayer>,
+}
+
+// A `[[sites]]` table, `id`, `hosts`, `articles_dir` and `site_url` can't be taken from the main
+// site
+#[derive(Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+struct SiteLayer {
+    id: String,
+    hosts: Vec<String>,
+    articles_dir: Option<PathBuf>,
+    authors_dir: Option<PathBuf>,
+    page_size: Option<usize>,
+    preview_lines: Option<usize>,
+    site_title: Option<String>,
+    site_url: Option<String>,
+    revision_author: Option<String>,
+    admin_token: Option<String>,
+    theme: Option<PathBuf>,
+    about: Option<PathBuf>,
+    features: FeaturesLayer,
+    extensions: ExtensionsLayer,
+}
+
+fn parse<T>(setting: &'static str, source: &str, value: &str) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e: T::Err| ConfigError::Invalid(setting, source.to_string(), e.to_string()))
+}
+
+// `comments,webmentions` turns both off
+fn disable_features(
+    features: &mut FeaturesLayer,
+    source: &str,
+    names: &str,
+) -> Result<(), ConfigError> {
+    for name in names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let feature = match name {
+            "comments" => &mut features.comments,
+            "webmentions" => &mut features.webmentions,
+            "playground" => &mut features.playground,
+            "analytics" => &mut features.analytics,
+            _ => {
+                return Err(ConfigError::Invalid(
+                    "features",
+                    source.to_string(),
+                    format!(
+                        "unknown feature `{}`, expected one of {}",
+                        name,
+                        FEATURES.join(", ")
+                    ),
+                ))
+            }
+        };
+        *feature = Some(false);
+    }
+    Ok(())
+}
+
+impl Layer {
+    // What applies to the main site
+    fn site(&self) -> SiteLayer {
+        SiteLayer {
+            id: String::from(MAIN_SITE_ID),
+            hosts: Vec::new(),
+            articles_dir: self.articles_dir.clone(),
+            authors_dir: self.authors_dir.clone(),
+            page_size: self.page_size,
+            preview_lines: self.preview_lines,
+            site_title: self.site_title.clone(),
+            site_url: self.site_url.clone(),
+            revision_author: self.revision_author.clone(),
+            admin_token: self.admin_token.clone(),
+            theme: self.theme.clone(),
+            about: self.about.clone(),
+            features: self.features.clone(),
+            extensions: self.extensions.clone(),
+        }
+    }
+
+    fn from_file(path: &Path) -> Result<Layer, ConfigError> {
+        let content =
+            fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+        toml::from_str(&content).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    // `PORT` and `SITE_URL` are what hosts like Heroku set, the rest follow the same naming
+    fn from_env() -> Result<Layer, ConfigError> {
+        let var = |name: &str| std::env::var(name).ok();
+        let mut layer = Layer {
+            bind_address: var("BIND_ADDRESS"),
+            static_dir: var("STATIC_DIR").map(PathBuf::from),
+            articles_dir: var("ARTICLES_DIR").map(PathBuf::from),
+            authors_dir: var("AUTHORS_DIR").map(PathBuf::from),
+            store_path: var("STORE_PATH").map(PathBuf::from),
+            site_title: var("SITE_TITLE"),
+            site_url: var("SITE_URL"),
+            revision_author: var("REVISION_AUTHOR"),
+            admin_token: var("ADMIN_TOKEN"),
+            playground_url: var("PLAYGROUND_URL"),
+            ..Layer::default()
+        };
+        if let Some(port) = var("PORT") {
+            layer.port = Some(parse("port", "PORT", &port)?);
+        }
+        if let Some(page_size) = var("PAGE_SIZE") {
+            layer.page_size = Some(parse("page_size", "PAGE_SIZE", &page_size)?);
+        }
+        if let Some(preview_lines) = var("PREVIEW_LINES") {
+            layer.preview_lines = Some(parse("preview_lines", "PREVIEW_LINES", &preview_lines)?);
+        }
+        if let Some(behind_proxy) = var("BEHIND_PROXY") {
+            layer.behind_proxy = Some(parse("behind_proxy", "BEHIND_PROXY", &behind_proxy)?);
+        }
+        if let Some(disabled) = var("DISABLED_FEATURES") {
+            disable_features(&mut layer.features, "DISABLED_FEATURES", &disabled)?;
+        }
+        Ok(layer)
+    }
+
+    fn from_args(args: &ArgMatches) -> Result<Layer, ConfigError> {
+        let arg = |name: &str| args.value_of(name).map(str::to_string);
+        let mut layer = Layer {
+            bind_address: arg("bind-address"),
+            static_dir: arg("static-dir").map(PathBuf::from),
+            articles_dir: arg("articles-dir").map(PathBuf::from),
+            authors_dir: arg("authors-dir").map(PathBuf::from),
+            store_path: arg("store-path").map(PathBuf::from),
+            site_title: arg("site-title"),
+            site_url: arg("site-url"),
+            ..Layer::default()
+        };
+        if let Some(port) = args.value_of("port") {
+            layer.port = Some(parse("port", "--port", port)?);
+        }
+        if let Some(page_size) = args.value_of("page-size") {
+            layer.page_size = Some(parse("page_size", "--page-size", page_size)?);
+        }
+        if let Some(preview_lines) = args.value_of("preview-lines") {
+            layer.preview_lines = Some(parse("preview_lines", "--preview-lines", preview_lines)?);
+        }
+        for disabled in args.values_of("disable").into_iter().flatten() {
+            disable_features(&mut layer.features, "--disable", disabled)?;
+        }
+        Ok(layer)
+    }
+}
+
+// A setting and the source it came from, for the errors
+#[derive(Clone)]
+struct Setting<T> {
+    value: T,
+    source: String,
+}
+
+impl<T> Setting<T> {
+    fn default(value: T) -> Setting<T> {
+        Setting {
+            value,
+            source: String::from("default"),
+        }
+    }
+
+    fn set(&mut self, value: Option<T>, source: &str) {
+        if let Some(value) = value {
+            self.value = value;
+            self.source = source.to_string();
+        }
+    }
+
+    fn invalid(&self, setting: &'static str, reason: impl Into<String>) -> ConfigError {
+        ConfigError::Invalid(setting, self.source.clone(), reason.into())
+    }
+}
+
+fn cli() -> App<'static, 'static> {
+    let value = |name: &'static str, value_name: &'static str, help: &'static str| {
+        Arg::with_name(name)
+            .long(name)
+            .value_name(value_name)
+            .takes_value(true)
+            .help(help)
+    };
+    App::new("server")
+        .about("Serves the blog")
+        .arg(value(
+            "config",
+            "FILE",
+            "TOML configuration file, `blog.toml` by default",
+        ))
+        .arg(value("bind-address", "IP", "Address to listen on"))
+        .arg(value("port", "PORT", "Port to listen on"))
+        .arg(value(
+            "static-dir",
+            "DIR",
+            "Directory with the client's files",
+        ))
+        .arg(value("articles-dir", "DIR", "Directory with the articles"))
+        .arg(value(
+            "authors-dir",
+            "DIR",
+            "Directory with the author profiles",
+        ))
+        .arg(value("store-path", "DIR", "Where the data is stored"))
+        .arg(value("page-size", "N", "Articles per page"))
+        .arg(value(
+            "preview-lines",
+            "N",
+            "Lines of every article in the list",
+        ))
+        .arg(value("site-title", "TITLE", "Title of the blog"))
+        .arg(value("site-url", "URL", "Public URL of the blog"))
+        .arg(
+            value("disable", "FEATURE", "Turns a feature off")
+                .multiple(true)
+                .number_of_values(1)
+                .possible_values(&FEATURES),
+        )
+        .subcommand(
+            SubCommand::with_name("check-links")
+                .about("Reports the broken links of the articles instead of serving them"),
+        )
+}
+
+impl Config {
+    pub fn load() -> Result<Config, ConfigError> {
+        let args = cli().get_matches();
+
+        let mut layers = Vec::new();
+        let explicit_file = args
+            .value_of("config")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var("BLOG_CONFIG").ok().map(PathBuf::from));
+        match explicit_file {
+            Some(path) => layers.push((path.display().to_string(), Layer::from_file(&path)?)),
+            // Only an explicitly given file has to exist
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => layers.push((
+                DEFAULT_CONFIG_FILE.to_string(),
+                Layer::from_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            )),
+            None => {}
+        }
+        layers.push((String::from("environment"), Layer::from_env()?));
+        layers.push((String::from("command line"), Layer::from_args(&args)?));
+
+        let mut config = Config::from_layers(layers)?;
+        config.check_links = args.subcommand_matches("check-links").is_some();
+        Ok(config)
+    }
+
+    fn from_layers(layers: Vec<(String, Layer)>) -> Result<Config, ConfigError> {
+        // Locally only this machine can reach the server
+        let mut bind_address = Setting::default(String::from(if cfg!(debug_assertions) {
+            "127.0.0.1"
+        } else {
+            "0.0.0.0"
+        }));
+        let mut port = Setting::default(DEFAULT_PORT);
+        let mut static_dir = Setting::default(PathBuf::from(constants::STATIC_URL));
+        let mut store_path = Setting::default(PathBuf::from(DEFAULT_STORE_PATH));
+        let mut playground_url = Setting::default(DEFAULT_PLAYGROUND_URL.to_string());
+        let mut behind_proxy = Setting::default(false);
+        let mut main_site = SiteSettings::default();
+        let mut sites = Vec::new();
+
+        for (source, layer) in layers {
+            bind_address.set(layer.bind_address.clone(), &source);
+            port.set(layer.port, &source);
+            static_dir.set(layer.static_dir.clone(), &source);
+            store_path.set(layer.store_path.clone(), &source);
+            playground_url.set(layer.playground_url.clone(), &source);
+            behind_proxy.set(layer.behind_proxy, &source);
+            main_site.apply(layer.site(), &source);
+            sites.extend(layer.sites.into_iter().map(|site| (source.clone(), site)));
+        }
+
+        if !static_dir.value.is_dir() {
+            return Err(static_dir.invalid("static_dir", "not a directory"));
+        }
+        // Where the client expects the articles' images, relative links wouldn't work anywhere
+        // else
+        if main_site.articles_dir.value.is_none() {
+            main_site.articles_dir.value = Some(static_dir.value.join(constants::ARTICLES_PATH));
+        }
+
+        // Later sites are built from the main site's settings, before they are consumed
+        let inherited = main_site.inherited();
+        let main_site = main_site.build(String::from(MAIN_SITE_ID), Vec::new())?;
+        let mut ids = HashSet::new();
+        let mut hosts = HashSet::new();
+        let sites = sites
+            .into_iter()
+            .map(|(source, layer)| {
+                let source = format!("{}, site `{}`", source, layer.id);
+                let id_is_valid = layer
+                    .id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+                if layer.id.is_empty() || !id_is_valid {
+                    return Err(ConfigError::Invalid(
+                        "id",
+                        source,
+                        String::from("must be letters, digits, `-` and `_`"),
+                    ));
+                }
+                if layer.id == MAIN_SITE_ID || !ids.insert(layer.id.clone()) {
+                    return Err(ConfigError::Invalid(
+                        "id",
+                        source,
+                        String::from("already taken"),
+                    ));
+                }
+                if layer.articles_dir.is_none() {
+                    return Err(ConfigError::Invalid(
+                        "articles_dir",
+                        source,
+                        String::from("every site needs its own articles"),
+                    ));
+                }
+                if layer.site_url.is_none() {
+                    return Err(ConfigError::Invalid(
+                        "site_url",
+                        source,
+                        String::from("every site needs its own public URL"),
+                    ));
+                }
+                if layer.hosts.is_empty() {
+                    return Err(ConfigError::Invalid(
+                        "hosts",
+                        source,
+                        String::from("no host would reach the site"),
+                    ));
+                }
+                let site_hosts = layer
+                    .hosts
+                    .iter()
+                    .map(|host| {
+                        let host = host.trim().to_lowercase();
+                        if host.is_empty() || host.contains(['/', ':']) {
+                            Err(ConfigError::Invalid(
+                                "hosts",
+                                source.clone(),
+                                format!("`{}` isn't a host name", host),
+                            ))
+                        } else if !hosts.insert(host.clone()) {
+                            Err(ConfigError::Invalid(
+                                "hosts",
+                                source.clone(),
+                                format!("`{}` is already served by another site", host),
+                            ))
+                        } else {
+                            Ok(host)
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let id = layer.id.clone();
+                let mut settings = inherited.clone();
+                settings.apply(layer, &source);
+                settings.build(id, site_hosts)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Config {
+            bind_address: bind_address
+                .value
+                .parse()
+                .map_err(|e: std::net::AddrParseError| {
+                    bind_address.invalid("bind_address", e.to_string())
+                })?,
+            port: port.value,
+            static_dir: static_dir.value,
+            store_path: store_path.value,
+            playground_url: parse_http_url(&playground_url, "playground_url")?,
+            behind_proxy: behind_proxy.value,
+            main_site,
+            sites,
+            check_links: false,
+        })
+    }
+}
+
+fn parse_http_url(setting: &Setting<String>, name: &'static str) -> Result<Url, ConfigError> {
+    let url = Url::parse(&setting.value).map_err(|e| setting.invalid(name, e.to_string()))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        _ => Err(setting.invalid(name, "must be an http or https URL")),
+    }
+}
+
+fn existing_file(
+    setting: &Setting<Option<PathBuf>>,
+    name: &'static str,
+) -> Result<Option<PathBuf>, ConfigError> {
+    match &setting.value {
+        Some(path) if !path.is_file() => Err(setting.invalid(name, "not a file")),
+        path => Ok(path.clone()),
+    }
+}
+
+// The settings of a site, before being validated
+#[derive(Clone)]
+struct SiteSettings {
+    articles_dir: Setting<Option<PathBuf>>,
+    authors_dir: Setting<Option<PathBuf>>,
+    page_size: Setting<usize>,
+    preview_lines: Setting<usize>,
+    site_title: Setting<String>,
+    site_url: Setting<String>,
+    revision_author: Setting<String>,
+    admin_token: Setting<Option<String>>,
+    theme: Setting<Option<PathBuf>>,
+    about: Setting<Option<PathBuf>>,
+    features: Features,
+    extensions: Extensions,
+}
+
+impl Default for SiteSettings {
+    fn default() -> SiteSettings {
+        SiteSettings {
+            articles_dir: Setting::default(None),
+            authors_dir: Setting::default(None),
+            page_size: Setting::default(DEFAULT_PAGE_SIZE),
+            preview_lines: Setting::default(DEFAULT_PREVIEW_LINES),
+            site_title: Setting::default(DEFAULT_SITE_TITLE.to_string()),
+            site_url: Setting::default(DEFAULT_SITE_URL.to_string()),
+            revision_author: Setting::default(DEFAULT_REVISION_AUTHOR.to_string()),
+            admin_token: Setting::default(None),
+            theme: Setting::default(None),
+            about: Setting::default(None),
+            features: Features {
+                comments: true,
+                webmentions: true,
+                playground: true,
+                analytics: true,
+            },
+            extensions: Extensions::default(),
+        }
+    }
+}
+
+impl SiteSettings {
+    // What other sites take from this one, the rest is theirs alone
+    fn inherited(&self) -> SiteSettings {
+        SiteSettings {
+            page_size: self.page_size.clone(),
+            preview_lines: self.preview_lines.clone(),
+            site_title: self.site_title.clone(),
+            revision_author: self.revision_author.clone(),
+            features: self.features,
+            extensions: self.extensions,
+            ..SiteSettings::default()
+        }
+    }
+
+    fn apply(&mut self, layer: SiteLayer, source: &str) {
+        self.articles_dir.set(layer.articles_dir.map(Some), source);
+        self.authors_dir.set(layer.authors_dir.map(Some), source);
+        self.page_size.set(layer.page_size, source);
+        self.preview_lines.set(layer.preview_lines, source);
+        self.site_title.set(layer.site_title, source);
+        self.site_url.set(layer.site_url, source);
+        self.revision_author.set(layer.revision_author, source);
+        self.admin_token.set(layer.admin_token.map(Some), source);
+        self.theme.set(layer.theme.map(Some), source);
+        self.about.set(layer.about.map(Some), source);
+
+        let features = layer.features;
+        self.features.comments = features.comments.unwrap_or(self.features.comments);
+        self.features.webmentions = features.webmentions.unwrap_or(self.features.webmentions);
+        self.features.playground = features.playground.unwrap_or(self.features.playground);
+        self.features.analytics = features.analytics.unwrap_or(self.features.analytics);
+
+        let extensions = layer.extensions;
+        let current = self.extensions;
+        self.extensions = Extensions {
+            tables: extensions.tables.unwrap_or(current.tables),
+            footnotes: extensions.footnotes.unwrap_or(current.footnotes),
+            strikethrough: extensions.strikethrough.unwrap_or(current.strikethrough),
+            task_lists: extensions.task_lists.unwrap_or(current.task_lists),
+            smart_punctuation: extensions
+                .smart_punctuation
+                .unwrap_or(current.smart_punctuation),
+            heading_attributes: extensions
+                .heading_attributes
+                .unwrap_or(current.heading_attributes),
+        };
+    }
+
+    fn build(self, id: String, hosts: Vec<String>) -> Result<SiteConfig, ConfigError> {
+        let articles_dir = match &self.articles_dir.value {
+            Some(dir) if dir.is_dir() => dir.clone(),
+            _ => return Err(self.articles_dir.invalid("articles_dir", "not a directory")),
+        };
+        // Only a directory set explicitly has to exist
+        let authors_dir = match &self.authors_dir.value {
+            Some(dir) if !dir.is_dir() => {
+                return Err(self.authors_dir.invalid("authors_dir", "not a directory"))
+            }
+            Some(dir) => Some(dir.clone()),
+            None => Some(articles_dir.join(AUTHORS_PATH)).filter(|dir| dir.is_dir()),
+        };
+        if self.page_size.value == 0 {
+            return Err(self.page_size.invalid("page_size", "must be at least 1"));
+        }
+        if self.preview_lines.value == 0 {
+            return Err(self
+                .preview_lines
+                .invalid("preview_lines", "must be at least 1"));
+        }
+        if self.site_title.value.trim().is_empty() {
+            return Err(self.site_title.invalid("site_title", "can't be empty"));
+        }
+
+        Ok(SiteConfig {
+            id,
+            hosts,
+            articles_dir,
+            authors_dir,
+            page_size: self.page_size.value,
+            preview_lines: self.preview_lines.value,
+            site_url: parse_http_url(&self.site_url, "site_url")?,
+            site_title: self.site_title.value,
+            revision_author: self.revision_author.value,
+            // An empty token would let anyone in
+            admin_token: self.admin_token.value.filter(|token| !token.is_empty()),
+            theme: existing_file(&self.theme, "theme")?,
+            about: existing_file(&self.about, "about")?,
+            features: self.features,
+            extensions: self.extensions,
+        })
+    }
+}
+
+#[cfg(test)]
+impl SiteConfig {
+    // The defaults but for what tells sites apart
+    pub fn for_tests(id: &str, hosts: &[&str], articles_dir: PathBuf) -> SiteConfig {
+        SiteSettings {
+            articles_dir: Setting::default(Some(articles_dir)),
+            ..SiteSettings::default()
+        }
+        .build(
+            id.to_string(),
+            hosts.iter().map(|host| host.to_string()).collect(),
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn layer(toml: &str) -> Layer {
+        toml::from_str(toml).unwrap()
+    }
+
+    // The static files with the main site's articles and what other sites need
+    fn dir() -> TempDir {
+        TempDir::with_files(&[
+            ("articles/main.md", "# Main"),
+            ("notes/note.md", "# Note"),
+            ("authors/jane.md", "Jane"),
+            ("theme.css", ""),
+            ("about.md", ""),
+        ])
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let dir = dir();
+        let file = layer(&format!(
+            "static_dir = {:?}\nport = 1\npage_size = 1\npreview_lines = 1\nsite_title = \"File\"\n\
+             [features]\ncomments = false\nplayground = false",
+            dir.path()
+        ));
+        let env = Layer {
+            port: Some(2),
+            page_size: Some(2),
+            features: FeaturesLayer {
+                webmentions: Some(false),
+                playground: Some(true),
+                ..FeaturesLayer::default()
+            },
+            ..Layer::default()
+        };
+        let args = Layer {
+            port: Some(3),
+            ..Layer::default()
+        };
+
+        let config = Config::from_layers(vec![
+            (String::from("blog.toml"), file),
+            (String::from("environment"), env),
+            (String::from("command line"), args),
+        ])
+        .unwrap();
+        assert_eq!(config.port, 3);
+        assert_eq!(config.main_site.page_size, 2);
+        assert_eq!(config.main_site.preview_lines, 1);
+        assert_eq!(config.main_site.site_title, "File");
+        assert_eq!(config.main_site.revision_author, DEFAULT_REVISION_AUTHOR);
+        assert_eq!(config.store_path, PathBuf::from(DEFAULT_STORE_PATH));
+        assert_eq!(
+            config.main_site.articles_dir,
+            dir.join(constants::ARTICLES_PATH)
+        );
+        assert_eq!(
+            config.main_site.features,
+            Features {
+                comments: false,
+                webmentions: false,
+                playground: true,
+                analytics: true,
+            }
+        );
+
+        // Errors point at the layer the invalid value came from
+        let error = Config::from_layers(vec![
+            (
+                String::from("blog.toml"),
+                layer(&format!("static_dir = {:?}", dir.path())),
+            ),
+            (
+                String::from("environment"),
+                Layer {
+                    page_size: Some(0),
+                    ..Layer::default()
+                },
+            ),
+        ])
+        .unwrap_err();
+        assert!(
+            matches!(&error, ConfigError::Invalid("page_size", source, _) if source == "environment"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn sites_only_inherit_what_is_meant_to_be_shared() {
+        let dir = dir();
+        let main_site = format!(
+            "static_dir = {dir:?}\nauthors_dir = {authors:?}\npage_size = 3\nsite_title = \"Main\"\n\
+             site_url = \"https://main.example.com/\"\nrevision_author = \"jane\"\n\
+             admin_token = \"secret\"\ntheme = {theme:?}\nabout = {about:?}\n\
+             [features]\ncomments = false\n[extensions]\ntables = false\n",
+            dir = dir.path(),
+            authors = dir.join("authors"),
+            theme = dir.join("theme.css"),
+            about = dir.join("about.md"),
+        );
+        let site = format!(
+            "[[sites]]\nid = \"notes\"\nhosts = [\"Notes.example.com\"]\narticles_dir = {:?}\n",
+            dir.join("notes")
+        );
+
+        let config = Config::from_layers(vec![(
+            String::from("blog.toml"),
+            layer(&format!(
+                "{}{}site_url = \"https://notes.example.com/\"\npreview_lines = 4",
+                main_site, site
+            )),
+        )])
+        .unwrap();
+        let notes = &config.sites[0];
+        assert_eq!(notes.hosts, vec!["notes.example.com"]);
+        assert_eq!(notes.site_url.as_str(), "https://notes.example.com/");
+        assert_eq!(notes.preview_lines, 4);
+        assert_eq!(notes.page_size, 3);
+        assert_eq!(notes.site_title, "Main");
+        assert_eq!(notes.revision_author, "jane");
+        assert!(!notes.features.comments);
+        assert!(!notes.extensions.tables);
+        assert!(notes.extensions.footnotes);
+        assert_eq!(notes.admin_token, None);
+        assert_eq!(notes.theme, None);
+        assert_eq!(notes.about, None);
+        assert_eq!(notes.authors_dir, None);
+        assert_eq!(config.main_site.admin_token.as_deref(), Some("secret"));
+
+        let error = Config::from_layers(vec![(
+            String::from("blog.toml"),
+            layer(&format!("{}{}", main_site, site)),
+        )])
+        .unwrap_err();
+        assert!(
+            matches!(&error, ConfigError::Invalid("site_url", source, _) if source.contains("notes")),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,432 @@
+use layout::backends::svg::SVGWriter;
+use layout::gv::parser::ast;
+use layout::gv::{DotParser, GraphBuilder};
+use shared::markdown::{self, Extensions};
+use std::collections::HashMap;
+use std::panic;
+
+// Fenced blocks tagged `svgbob`, `dot` or `mermaid` are replaced by the SVG they describe, the
+// same way math is prerendered, so diagrams don't need any JS either. A diagram that can't be
+// rendered is left as a code block.
+
+fn render_svgbob(source: &str) -> Result<String, String> {
+    Ok(svgbob::to_svg(source))
+}
+
+// The layout only leaves out the arrowheads of `--` edges, which are only allowed in undirected
+// graphs, so the attributes doing that in directed ones are turned into those
+fn remove_arrowheads(statements: &mut [ast::Stmt]) {
+    for statement in statements {
+        match statement {
+            ast::Stmt::Edge(edge) => {
+                let no_arrowheads = edge.list.iter().any(|(name, value)| {
+                    matches!(
+                        (name.as_str(), value.as_str()),
+                        ("arrowhead", "none") | ("dir", "none")
+                    )
+                });
+                if no_arrowheads {
+                    for (_, kind) in &mut edge.to {
+                        *kind = ast::ArrowKind::Line;
+                    }
+                }
+            }
+            ast::Stmt::SubGraph(graph) => remove_arrowheads(&mut graph.list.list),
+            _ => {}
+        }
+    }
+}
+
+fn render_dot(source: &str) -> Result<String, String> {
+    let mut graph = DotParser::new(source).process()?;
+    remove_arrowheads(&mut graph.list.list);
+    let mut builder = GraphBuilder::new();
+    builder.visit_graph(&graph);
+    let mut visual_graph = builder.get();
+    if visual_graph.num_nodes() == 0 {
+        return Err("Empty graph".to_string());
+    }
+
+    let mut svg = SVGWriter::new();
+    visual_graph.do_it(false, false, false, &mut svg);
+    Ok(svg.finalize())
+}
+
+fn render_mermaid(source: &str) -> Result<String, String> {
+    render_dot(&mermaid::to_dot(source)?)
+}
+
+// The XML prolog isn't allowed inside HTML and a blank line would end the HTML block early. In a
+// list item or a quote every line needs the prefix the code block had, and the HTML block ends
+// with a blank line of its own so what follows isn't swallowed by it.
+fn inline_svg(svg: &str, prefix: &str) -> String {
+    let svg = svg.find("<svg").map_or(svg, |start| &svg[start..]);
+    let lines: Vec<&str> = svg.lines().filter(|l| !l.trim().is_empty()).collect();
+    format!(
+        "<div class=\"diagram\">{}</div>\n{}",
+        lines.join(&format!("\n{}", prefix)),
+        prefix
+    )
+}
+
+// What comes before the opening fence on its line, with list markers turned into spaces so it
+// can start the lines that follow
+fn continuation(content: &str, start: usize) -> String {
+    let line_start = content[..start].rfind('\n').map_or(0, |n| n + 1);
+    content[line_start..start]
+        .chars()
+        .map(|c| {
+            if c == '>' || c.is_whitespace() {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+fn render(lang: &str, source: &str) -> Option<String> {
+    let render = match lang {
+        "svgbob" => render_svgbob,
+        "dot" => render_dot,
+        "mermaid" => render_mermaid,
+        _ => return None,
+    };
+
+    // The layout panics on some graphs, those are just another diagram that can't be rendered
+    let result = panic::catch_unwind(|| render(source))
+        .unwrap_or_else(|_| Err("the layout panicked".to_string()));
+    match result {
+        Ok(svg) => Some(svg),
+        Err(e) => {
+            println!("Couldn't render {} diagram: {}", lang, e);
+            None
+        }
+    }
+}
+
+pub fn render_diagrams(content: &str, extensions: Extensions) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut copied = 0;
+    for block in markdown::code_blocks(content, extensions) {
+        let lang = block
+            .info
+            .as_deref()
+            .and_then(|info| info.split_whitespace().next());
+        if let Some(svg) = lang.and_then(|lang| render(lang, &block.code)) {
+            output.push_str(&content[copied..block.range.start]);
+            output.push_str(&inline_svg(&svg, &continuation(content, block.range.start)));
+            copied = block.range.end;
+        }
+    }
+    output.push_str(&content[copied..]);
+    output
+}
+
+// Just enough of mermaid's flowchart syntax to be useful: `graph`/`flowchart` with a direction,
+// nodes with `[box]`, `(round)`, `{diamond}` or `((circle))` shapes and `-->`, `---`, `-.->`
+// and `==>` links with optional `|labels|` or `-- labels -->`. It's translated to DOT.
+mod mermaid {
+    use super::HashMap;
+
+    struct Node {
+        label: String,
+        shape: &'static str,
+    }
+
+    struct Edge {
+        from: String,
+        to: String,
+        label: String,
+        arrow: bool,
+        style: &'static str,
+    }
+
+    #[derive(Default)]
+    struct Graph {
+        order: Vec<String>,
+        nodes: HashMap<String, Node>,
+        edges: Vec<Edge>,
+    }
+
+    // Links as (syntax, has an arrow, DOT attributes), longest first so prefixes don't win
+    const LINKS: [(&str, bool, &str); 6] = [
+        ("-.->", true, "style=dashed"),
+        ("-.-", false, "style=dashed"),
+        ("==>", true, "penwidth=3"),
+        ("===", false, "penwidth=3"),
+        ("-->", true, ""),
+        ("---", false, ""),
+    ];
+
+    // Same links with text in the middle, as (opening half, closing half, has an arrow, attributes)
+    type TextLink = (&'static str, &'static str, bool, &'static str);
+    const TEXT_LINKS: [TextLink; 6] = [
+        ("--", "-->", true, ""),
+        ("--", "---", false, ""),
+        ("-.", ".->", true, "style=dashed"),
+        ("-.", ".-", false, "style=dashed"),
+        ("==", "==>", true, "penwidth=3"),
+        ("==", "===", false, "penwidth=3"),
+    ];
+
+    // Opening and closing delimiters for each shape, `((` before `(` for the same reason
+    const SHAPES: [(&str, &str, &str); 4] = [
+        ("((", "))", "circle"),
+        ("[", "]", "box"),
+        ("(", ")", "box"),
+        ("{", "}", "box"),
+    ];
+
+    fn quote(text: &str) -> String {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    fn unquote(text: &str) -> String {
+        let text = text.trim();
+        text.strip_prefix('"')
+            .and_then(|t| t.strip_suffix('"'))
+            .unwrap_or(text)
+            .to_string()
+    }
+
+    impl Graph {
+        // Reads a node at the start of `statement`, returning its id and the rest of the line
+        fn node<'a>(&mut self, statement: &'a str) -> Result<(String, &'a str), String> {
+            let statement = statement.trim_start();
+            let id_end = statement
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(statement.len());
+            if id_end == 0 {
+                return Err(format!("Expected a node in `{}`", statement));
+            }
+
+            let id = statement[..id_end].to_string();
+            let mut rest = &statement[id_end..];
+            let shape = SHAPES.iter().find(|(open, _, _)| rest.starts_with(open));
+            if let Some((open, close, shape)) = shape {
+                let end = rest
+                    .find(close)
+                    .ok_or_else(|| format!("Unclosed node `{}`", id))?;
+                let label = unquote(&rest[open.len()..end]);
+                self.nodes.insert(id.clone(), Node { label, shape });
+                rest = &rest[end + close.len()..];
+            }
+
+            if !self.order.contains(&id) {
+                self.order.push(id.clone());
+            }
+            Ok((id, rest))
+        }
+
+        // Reads a link, returning (has an arrow, attributes, label, rest of the line)
+        fn link(statement: &str) -> Option<(bool, &'static str, String, &str)> {
+            let statement = statement.trim_start();
+            let (syntax, arrow, style) = LINKS.iter().find(|(s, _, _)| statement.starts_with(s))?;
+            let rest = &statement[syntax.len()..];
+            match rest.strip_prefix('|') {
+                Some(labeled) => {
+                    let end = labeled.find('|')?;
+                    Some((*arrow, style, unquote(&labeled[..end]), &labeled[end + 1..]))
+                }
+                None => Some((*arrow, style, String::new(), rest)),
+            }
+        }
+
+        // `A -- text --> B`, the text goes between the two halves of the link
+        fn text_link(statement: &str) -> Option<(bool, &'static str, String, &str)> {
+            let statement = statement.trim_start();
+            let mut found: Option<(usize, &TextLink)> = None;
+            for link in TEXT_LINKS.iter() {
+                let rest = match statement.strip_prefix(link.0) {
+                    Some(rest) if rest.starts_with(char::is_whitespace) => rest,
+                    _ => continue,
+                };
+                if let Some(end) = rest.find(&format!(" {}", link.1)) {
+                    if !matches!(found, Some((closest, _)) if closest <= end) {
+                        found = Some((end, link));
+                    }
+                }
+            }
+
+            let (end, (open, close, arrow, style)) = found?;
+            let rest = &statement[open.len()..];
+            let label = unquote(&rest[..end]);
+            Some((*arrow, style, label, &rest[end + 1 + close.len()..]))
+        }
+
+        fn statement(&mut self, statement: &str) -> Result<(), String> {
+            let (mut from, mut rest) = self.node(statement)?;
+            while !rest.trim().is_empty() {
+                let (arrow, style, label, after) = Graph::link(rest)
+                    .or_else(|| Graph::text_link(rest))
+                    .ok_or_else(|| format!("Unsupported link in `{}`", statement))?;
+                let (to, after) = self.node(after)?;
+                self.edges.push(Edge {
+                    from,
+                    to: to.clone(),
+                    label,
+                    arrow,
+                    style,
+                });
+                from = to;
+                rest = after;
+            }
+            Ok(())
+        }
+
+        fn to_dot(&self, direction: &str) -> String {
+            let mut dot = format!("digraph {{\nrankdir={};\n", direction);
+            for id in &self.order {
+                let (label, shape) = match self.nodes.get(id) {
+                    Some(node) => (node.label.as_str(), node.shape),
+                    None => (id.as_str(), "box"),
+                };
+                dot += &format!("{} [label={}, shape={}];\n", quote(id), quote(label), shape);
+            }
+
+            for edge in &self.edges {
+                let mut attributes = vec![format!("label={}", quote(&edge.label))];
+                if !edge.style.is_empty() {
+                    attributes.push(edge.style.to_string());
+                }
+                // `--` isn't allowed in a digraph, links without an arrow just don't draw it
+                if !edge.arrow {
+                    attributes.push(String::from("arrowhead=none"));
+                }
+                dot += &format!(
+                    "{} -> {} [{}];\n",
+                    quote(&edge.from),
+                    quote(&edge.to),
+                    attributes.join(", ")
+                );
+            }
+            dot + "}\n"
+        }
+    }
+
+    pub fn to_dot(source: &str) -> Result<String, String> {
+        let mut statements = source
+            .lines()
+            .flat_map(|line| line.split(';'))
+            .map(str::trim)
+            .filter(|s| !s.is_empty() && !s.starts_with("%%"));
+
+        let header = statements.next().unwrap_or("");
+        let mut header = header.split_whitespace();
+        if !matches!(header.next(), Some("graph") | Some("flowchart")) {
+            return Err("Only flowcharts are supported".to_string());
+        }
+        let direction = match header.next() {
+            Some("LR") | Some("RL") => "LR",
+            _ => "TB",
+        };
+
+        let mut graph = Graph::default();
+        for statement in statements {
+            let keyword = statement.split_whitespace().next().unwrap_or("");
+            // Styling and grouping are ignored, the nodes are still drawn
+            if matches!(
+                keyword,
+                "subgraph"
+                    | "end"
+                    | "direction"
+                    | "style"
+                    | "classDef"
+                    | "class"
+                    | "linkStyle"
+                    | "click"
+            ) {
+                continue;
+            }
+            graph.statement(statement)?;
+        }
+
+        Ok(graph.to_dot(direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark as pc;
+
+    #[test]
+    fn links_without_arrows_are_still_directed_edges() {
+        let dot = mermaid::to_dot("graph LR\nA[Start] --- B\nB -- text --- C\nC --> A").unwrap();
+        assert!(!dot.contains(" -- "), "{}", dot);
+        assert!(
+            dot.contains("\"A\" -> \"B\" [label=\"\", arrowhead=none];"),
+            "{}",
+            dot
+        );
+        assert!(
+            dot.contains("\"B\" -> \"C\" [label=\"text\", arrowhead=none];"),
+            "{}",
+            dot
+        );
+        assert!(dot.contains("\"C\" -> \"A\" [label=\"\"];"), "{}", dot);
+        assert!(render_dot(&dot).is_ok());
+
+        let svg = render_mermaid("graph TD\nA --- B").unwrap();
+        assert!(!svg.contains("marker-end"), "{}", svg);
+    }
+
+    #[test]
+    fn diagrams_that_cant_be_rendered_stay_code_blocks() {
+        let content = "```dot\nnot a graph\n```\n\n```mermaid\ngraph TD\nA --> B\n```\n";
+        let rendered = render_diagrams(content, Extensions::default());
+        assert!(
+            rendered.starts_with("```dot\nnot a graph\n```\n"),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains("<div class=\"diagram\"><svg"),
+            "{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn diagrams_inside_other_code_blocks_are_left_alone() {
+        let content = "````md\n```dot\ndigraph { a -> b }\n```\n````\n\n\
+            ~~~\n```mermaid\ngraph TD\nA --> B\n```\n~~~\n";
+        assert_eq!(
+            render_diagrams(content, Extensions::default()),
+            content,
+            "{}",
+            content
+        );
+    }
+
+    #[test]
+    fn diagrams_stay_in_their_list_item_or_quote() {
+        let cases = [
+            "- Item\n\n  ```dot\n  digraph { a -> b }\n  ```\n\n  After\n",
+            "1. ```dot\n   digraph { a -> b }\n   ```\n   After\n",
+            "> ```dot\n> digraph { a -> b }\n> ```\n> After\n",
+        ];
+        for &content in &cases {
+            let rendered = render_diagrams(content, Extensions::default());
+            let mut depth = 0;
+            let mut diagram = false;
+            let mut after = false;
+            for event in markdown::parser(&rendered, Extensions::default()) {
+                match event {
+                    pc::Event::Start(pc::Tag::Item) | pc::Event::Start(pc::Tag::BlockQuote) => {
+                        depth += 1
+                    }
+                    pc::Event::End(pc::Tag::Item) | pc::Event::End(pc::Tag::BlockQuote) => {
+                        depth -= 1
+                    }
+                    pc::Event::Html(html) if html.contains("</svg>") => diagram = depth > 0,
+                    pc::Event::Text(text) if text.as_ref() == "After" => after = depth > 0,
+                    pc::Event::Html(html) => assert!(!html.contains("After"), "{}", rendered),
+                    _ => {}
+                }
+            }
+            assert!(diagram && after, "{}", rendered);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::clock;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use shared::errors::ApiError;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Replacements for actix's `error::ErrorXXX` helpers answering with a JSON `ApiError`, so the
+// client can tell errors apart. Internal errors only send an id to the client, what went wrong is
+// logged with that id.
+
+#[derive(Debug)]
+struct JsonError {
+    status: StatusCode,
+    message: String,
+    id: Option<String>,
+}
+
+static ERROR_COUNT: AtomicU64 = AtomicU64::new(0);
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.status, self.message)
+    }
+}
+
+impl ResponseError for JsonError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status).json(ApiError {
+            status: self.status.as_u16(),
+            message: self.message.clone(),
+            id: self.id.clone(),
+        })
+    }
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> actix_web::Error {
+    JsonError {
+        status,
+        message: message.into(),
+        id: None,
+    }
+    .into()
+}
+
+pub fn bad_request(message: impl Into<String>) -> actix_web::Error {
+    error(StatusCode::BAD_REQUEST, message)
+}
+
+pub fn unauthorized(message: impl Into<String>) -> actix_web::Error {
+    error(StatusCode::UNAUTHORIZED, message)
+}
+
+pub fn forbidden(message: impl Into<String>) -> actix_web::Error {
+    error(StatusCode::FORBIDDEN, message)
+}
+
+pub fn not_found(message: impl Into<String>) -> actix_web::Error {
+    error(StatusCode::NOT_FOUND, message)
+}
+
+pub fn too_many_requests(message: impl Into<String>) -> actix_web::Error {
+    error(StatusCode::TOO_MANY_REQUESTS, message)
+}
+
+pub fn bad_gateway(message: impl Into<String>) -> actix_web::Error {
+    error(StatusCode::BAD_GATEWAY, message)
+}
+
+// The id is unique for the lifetime of the process, the timestamp tells restarts apart
+pub fn internal(cause: impl fmt::Display) -> actix_web::Error {
+    let id = format!(
+        "{:x}-{:x}",
+        clock::now(),
+        ERROR_COUNT.fetch_add(1, Ordering::Relaxed)
+    );
+    println!("Internal error {}: {}", id, cause);
+    JsonError {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        message: String::from("Something went wrong on our side"),
+        id: Some(id),
+    }
+    .into()
+}
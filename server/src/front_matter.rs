@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+// Optional block of `key: value` lines at the very start of an article:
+//
+//     ---
+//     title: A better title than the first heading
+//     tags: rust, yew
+//     date: 2021-02-14
+//     order: 2
+//     ---
+//
+// Lists are comma separated, optionally between brackets. Unknown keys are ignored.
+
+const DELIMITER: &str = "---";
+
+#[derive(Debug, Default, Clone)]
+pub struct FrontMatter {
+    fields: HashMap<String, String>,
+}
+
+impl FrontMatter {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(String::as_str)
+    }
+
+    pub fn list(&self, key: &str) -> Vec<String> {
+        self.get(key)
+            .map(|value| value.trim_start_matches('[').trim_end_matches(']'))
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|item| item.trim().trim_matches('"').to_string())
+                    .filter(|item| !item.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn parse(block: &str) -> Option<FrontMatter> {
+    let mut fields = HashMap::new();
+    for line in block.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line.split_at(line.find(':')?);
+        let value = value[1..].trim().trim_matches('"');
+        fields.insert(key.trim().to_lowercase(), value.to_string());
+    }
+    Some(FrontMatter { fields })
+}
+
+// The front matter and the article without it. Anything that doesn't look like front matter is
+// left as part of the article, a leading `---` is also a valid thematic break.
+pub fn split(content: &str) -> (FrontMatter, &str) {
+    let not_front_matter = (FrontMatter::default(), content);
+    let first_line_end = match content.find('\n') {
+        Some(end) if content[..end].trim_end() == DELIMITER => end + 1,
+        _ => return not_front_matter,
+    };
+
+    let mut line_start = first_line_end;
+    for line in content[first_line_end..].split_inclusive('\n') {
+        if line.trim_end() == DELIMITER {
+            return match parse(&content[first_line_end..line_start]) {
+                Some(front_matter) => (front_matter, &content[line_start + line.len()..]),
+                None => not_front_matter,
+            };
+        }
+        line_start += line.len();
+    }
+    not_front_matter
+}
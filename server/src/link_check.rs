@@ -0,0 +1,163 @@
+use crate::articles::{self, Article, ArticleIndex};
+use pulldown_cmark as pc;
+use shared::constants;
+use shared::markdown;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use url::Url;
+
+// Checks every link and image of the articles pointing to the blog itself: other articles
+// (`#articles/<name>`), their headings(`#articles/<name>/<heading>` or just `#<heading>` within
+// the same article) and files served next to them(`articles/img/...`). External links aren't
+// checked.
+
+pub struct BrokenLink {
+    pub article: String,
+    pub line: usize,
+    pub target: String,
+    pub reason: String,
+}
+
+impl fmt::Display for BrokenLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: broken link `{}`: {}",
+            self.article, self.line, self.target, self.reason
+        )
+    }
+}
+
+fn decode(text: &str) -> String {
+    percent_encoding::percent_decode_str(text)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+struct Checker<'a> {
+    index: &'a ArticleIndex,
+    static_dir: &'a Path,
+    articles_dir: &'a Path,
+    heading_ids: HashMap<&'a str, Vec<String>>,
+}
+
+impl<'a> Checker<'a> {
+    fn check_heading(&self, article: &str, id: &str) -> Result<(), String> {
+        let ids = self.heading_ids.get(article).map_or(&[][..], Vec::as_slice);
+        if ids.iter().any(|known| *known == decode(id)) {
+            Ok(())
+        } else {
+            Err(format!("no heading `{}` in `{}`", id, article))
+        }
+    }
+
+    fn check(&self, article: &str, target: &str) -> Result<(), String> {
+        // Anything with a scheme(http, mailto...) is external
+        if Url::parse(target).is_ok() || target.starts_with("//") {
+            return Ok(());
+        }
+
+        if let Some((name, heading)) = articles::article_route(target) {
+            if self.index.get(&name).is_none() {
+                return Err(format!("no article `{}`", name));
+            }
+            return match heading {
+                Some(heading) => self.check_heading(&name, heading),
+                None => Ok(()),
+            };
+        }
+        // The renderer turns these into links to the article's headings
+        if let Some(heading) = target.strip_prefix('#') {
+            return self.check_heading(article, heading);
+        }
+
+        let path = target.split(['#', '?']).next().unwrap_or("");
+        let path = decode(path.trim_start_matches('/'));
+        let file = match path.strip_prefix(&format!("{}/", constants::ARTICLES_PATH)) {
+            Some(file) => self.articles_dir.join(file),
+            None => self.static_dir.join(&path),
+        };
+        if path.is_empty() || file.exists() {
+            Ok(())
+        } else {
+            Err(format!("no file `{}`", path))
+        }
+    }
+
+    fn check_article(&self, article: &Article) -> Vec<BrokenLink> {
+        let name = &article.metadata.name;
+        let content = &article.content;
+        markdown::parser(content, self.index.extensions())
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                pc::Event::Start(pc::Tag::Link(_, target, _))
+                | pc::Event::Start(pc::Tag::Image(_, target, _)) => {
+                    let reason = self.check(name, &target).err()?;
+                    Some(BrokenLink {
+                        article: name.clone(),
+                        line: content[..range.start].matches('\n').count() + 1,
+                        target: target.to_string(),
+                        reason,
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+// Relative links are resolved against the files the site serves: `articles/...` from
+// `articles_dir` and everything else from `static_dir`
+pub fn check_links(
+    index: &ArticleIndex,
+    static_dir: &Path,
+    articles_dir: &Path,
+) -> Vec<BrokenLink> {
+    let checker = Checker {
+        index,
+        static_dir,
+        articles_dir,
+        heading_ids: index
+            .articles()
+            .map(|article| {
+                let ids = markdown::heading_ids(article.body(), index.extensions());
+                (article.metadata.name.as_str(), ids)
+            })
+            .collect(),
+    };
+
+    index
+        .articles()
+        .flat_map(|article| checker.check_article(article))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+    use shared::markdown::Extensions;
+
+    #[test]
+    fn files_next_to_the_articles_are_looked_up_in_the_site_articles_dir() {
+        let dir = TempDir::with_files(&[
+            ("static/imgs/logo.png", ""),
+            ("articles/img/own.png", ""),
+            (
+                "articles/a.md",
+                "# A\n\n![](articles/img/own.png) ![](imgs/logo.png)\n\n\
+                 ![](articles/img/missing.png) ![](articles/a.md)",
+            ),
+        ]);
+        let static_dir = dir.join("static");
+        let articles_dir = dir.join("articles");
+
+        let index = ArticleIndex::load(&articles_dir, Extensions::default()).unwrap();
+        let broken: Vec<_> = check_links(&index, &static_dir, &articles_dir)
+            .into_iter()
+            .map(|link| link.target)
+            .collect();
+        assert_eq!(broken, vec!["articles/img/missing.png"]);
+    }
+}
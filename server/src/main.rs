@@ -1,99 +1,244 @@
 use actix_files as afs;
-use actix_http::Response;
-use actix_web::{
-    dev::HttpResponseBuilder, dev::ServiceResponse, http::StatusCode, middleware, web, App,
-    HttpRequest, HttpServer, Result,
-};
+use actix_web::{dev::Service, middleware, web, App, HttpServer};
 
-use shared::article_list::Articles;
+use articles::ArticleIndex;
+use config::{Config, Features, MAIN_SITE_ID};
+use playground::Playground;
+use rate_limit::{BehindProxy, RateLimiter};
 use shared::constants;
-use std::fs;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use sites::{SiteState, Sites};
+use std::time::Duration;
+use store::Store;
+use webmention::Verifier;
 
-const PREVIEW_LINES: i8 = 9;
+mod admin;
+mod analytics;
+mod articles;
+mod authors;
+mod clock;
+mod comments;
+mod config;
+mod diagrams;
+mod errors;
+mod front_matter;
+mod link_check;
+mod math;
+mod playground;
+mod rate_limit;
+mod revisions;
+mod shortcodes;
+mod sites;
+mod store;
+#[cfg(test)]
+mod temp_dir;
+mod webmention;
 
-// TODO: Cache in-memoy articles: Redis? Embedded KV store?
+const COMMENTS_PER_WINDOW: usize = 3;
+const COMMENTS_WINDOW: Duration = Duration::from_secs(10 * 60);
+const PLAYGROUND_RUNS_PER_WINDOW: usize = 10;
+const PLAYGROUND_WINDOW: Duration = Duration::from_secs(60);
+const WEBMENTIONS_PER_WINDOW: usize = 10;
+const WEBMENTIONS_WINDOW: Duration = Duration::from_secs(60);
+const MAX_WEBMENTIONS_IN_FLIGHT: usize = 16;
 
-// TODO implement own list_articles so we can do it async and use tokio::fs instead of blocking the
-// thread. THIS HERE NOW IS A BAD IDEA!!!!!!
-fn list_articles(
-    dir: &afs::Directory,
-    req: &HttpRequest,
-) -> Result<ServiceResponse, std::io::Error> {
-    let mut articles: Vec<_> = fs::read_dir(&dir.path)?.collect();
-    articles.sort_by(|a, b| {
-        a.as_ref()
-            .unwrap()
-            .file_name()
-            .cmp(&b.as_ref().unwrap().file_name())
-    });
-
-    let articles: Vec<_> = articles
-        .iter()
-        .filter(|f| !f.as_ref().unwrap().file_type().unwrap().is_dir())
-        .map(|res| res.as_ref().unwrap().file_name().into_string().unwrap())
-        .collect();
-
-    Ok(ServiceResponse::new(
-        req.clone(),
-        HttpResponseBuilder::new(StatusCode::OK).json(Articles { articles }),
-    ))
+// Requests the extractors can't make sense of get the same JSON errors as the handlers' ones
+fn extractor_error(e: impl std::fmt::Display) -> actix_web::Error {
+    errors::bad_request(e.to_string())
 }
 
-async fn preview(req: HttpRequest) -> Result<Response> {
-    let base_path = PathBuf::from(format!(
-        "{}/{}/",
-        constants::STATIC_URL,
-        constants::ARTICLES_PATH
-    ));
-    let path: PathBuf = req.match_info().query("filename").parse().unwrap();
-    let path = base_path.join(path);
-    let file = afs::NamedFile::open(path)?;
-    let res_buf = BufReader::new(&*file);
-    let mut iter = 0;
-    let mut buf = "".to_string();
+// Routes of the features that can be turned off, for sites with them off it's as if they didn't
+// exist
+fn feature_routes(sites: web::Data<Sites>, cfg: &mut web::ServiceConfig) {
+    let feature = |enabled: fn(&Features) -> bool| {
+        sites::guard(sites.clone(), move |site| enabled(&site.config.features))
+    };
+    cfg.service(
+        web::resource(format!("{}/{{article}}", constants::COMMENTS_URI))
+            .guard(feature(|features| features.comments))
+            .route(web::get().to(comments::list))
+            .route(web::post().to(comments::post)),
+    )
+    .service(
+        web::resource(constants::MODERATION_URI)
+            .guard(feature(|features| features.comments))
+            .route(web::get().to(comments::moderation_queue)),
+    )
+    .service(
+        web::resource(format!("{}/{{article}}/{{id}}", constants::MODERATION_URI))
+            .guard(feature(|features| features.comments))
+            .route(web::post().to(comments::moderate)),
+    )
+    .service(
+        web::resource(constants::STATS_URI)
+            .guard(feature(|features| features.analytics))
+            .route(web::get().to(analytics::stats)),
+    )
+    .service(
+        web::resource(constants::PLAYGROUND_URI)
+            .guard(feature(|features| features.playground))
+            .route(web::post().to(playground::execute)),
+    )
+    .route(
+        constants::PLAYGROUND_URI,
+        web::post().to(playground::disabled),
+    )
+    .service(
+        web::resource(constants::WEBMENTION_URI)
+            .guard(feature(|features| features.webmentions))
+            .route(web::post().to(webmention::receive)),
+    )
+    .service(
+        web::resource(format!("{}/{{article}}", constants::WEBMENTIONS_URI))
+            .guard(feature(|features| features.webmentions))
+            .route(web::get().to(webmention::list)),
+    );
+}
 
-    for line in res_buf.lines() {
-        if iter <= PREVIEW_LINES {
-            buf += &line?;
-            buf += "\n";
-            iter += 1;
-        }
+// The files next to the articles of the other sites, like their images. Those of the main site
+// are part of the static files.
+fn article_files(sites: web::Data<Sites>, cfg: &mut web::ServiceConfig) {
+    for site in sites.all().iter().skip(1) {
+        let id = site.config.id.clone();
+        cfg.service(
+            web::scope(&format!("/{}", constants::ARTICLES_PATH))
+                .guard(sites::guard(sites.clone(), move |site| {
+                    site.config.id == id
+                }))
+                .service(afs::Files::new("/", &site.config.articles_dir)),
+        );
     }
-
-    let mut res = Response::build_from(file.into_response(&req).unwrap());
-    let res = res.body(buf);
-    Ok(res)
 }
 
 #[actix_rt::main]
 async fn main() -> std::io::Result<()> {
-    let ip = if cfg!(debug_assertions) {
-        "127.0.0.1"
-    } else {
-        "0.0.0.0"
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(2);
+        }
     };
-    let binding_ip = format!(
-        "{}:{}",
-        ip,
-        std::env::var("PORT").unwrap_or_else(|_| "8080".to_string())
-    );
+    let binding_ip = format!("{}:{}", config.bind_address, config.port);
+
+    if config.check_links {
+        let mut broken_links = 0;
+        for site in std::iter::once(&config.main_site).chain(&config.sites) {
+            let index = ArticleIndex::load(&site.articles_dir, site.extensions)?;
+            // The files next to the main site's articles are served as part of the static ones
+            let articles_dir = if site.id == MAIN_SITE_ID {
+                config.static_dir.join(constants::ARTICLES_PATH)
+            } else {
+                site.articles_dir.clone()
+            };
+            let broken = link_check::check_links(&index, &config.static_dir, &articles_dir);
+            for link in &broken {
+                if config.sites.is_empty() {
+                    println!("{}", link);
+                } else {
+                    println!("{}: {}", site.id, link);
+                }
+            }
+            broken_links += broken.len();
+        }
+        std::process::exit(if broken_links == 0 { 0 } else { 1 });
+    }
+
+    let store = Store::open(&config.store_path).map_err(std::io::Error::other)?;
+    let (main_site, main_changes) = SiteState::load(config.main_site.clone(), &store)?;
+    let mut changes = vec![main_changes];
+    let mut sites = Vec::new();
+    for site in &config.sites {
+        let (site, changed_articles) = SiteState::load(site.clone(), &store)?;
+        sites.push(site);
+        changes.push(changed_articles);
+    }
+    let sites = web::Data::new(Sites::new(main_site, sites));
+
+    for (site, changed_articles) in sites.all().iter().zip(changes) {
+        // Don't ping the world every time we run locally
+        if site.config.features.webmentions && !cfg!(debug_assertions) {
+            actix_rt::spawn(webmention::send_webmentions(site.clone(), changed_articles));
+        }
+    }
+
+    let comments_rate_limiter =
+        web::Data::new(RateLimiter::new(COMMENTS_PER_WINDOW, COMMENTS_WINDOW));
+    let playground = web::Data::new(Playground {
+        url: config.playground_url.clone(),
+        rate_limiter: RateLimiter::new(PLAYGROUND_RUNS_PER_WINDOW, PLAYGROUND_WINDOW),
+    });
+    let webmention_verifier = web::Data::new(Verifier::new(
+        RateLimiter::new(WEBMENTIONS_PER_WINDOW, WEBMENTIONS_WINDOW),
+        MAX_WEBMENTIONS_IN_FLIGHT,
+    ));
+    let behind_proxy = web::Data::new(BehindProxy(config.behind_proxy));
+    let static_dir = config.static_dir.clone();
 
     println!("Will attemp to listen in http://{}/", binding_ip);
-    HttpServer::new(|| {
+    HttpServer::new(move || {
         App::new()
+            .app_data(sites.clone())
+            .app_data(comments_rate_limiter.clone())
+            .app_data(playground.clone())
+            .app_data(webmention_verifier.clone())
+            .app_data(behind_proxy.clone())
+            .app_data(web::PathConfig::default().error_handler(|e, _| extractor_error(e)))
+            .app_data(web::QueryConfig::default().error_handler(|e, _| extractor_error(e)))
+            .app_data(web::JsonConfig::default().error_handler(|e, _| extractor_error(e)))
+            .app_data(web::FormConfig::default().error_handler(|e, _| extractor_error(e)))
             .wrap(middleware::Compress::default())
-            .service(
-                afs::Files::new(
-                    constants::ARTICLE_LIST_URI,
-                    format!("{}/{}", constants::STATIC_URL, constants::ARTICLES_PATH),
-                )
-                .files_listing_renderer(list_articles)
-                .show_files_listing(),
+            .wrap_fn(|req, srv| {
+                let site = req
+                    .app_data::<web::Data<Sites>>()
+                    .map(|sites| sites.for_request(req.head()))
+                    .filter(|site| site.config.features.analytics);
+                let view = site.as_ref().and_then(|_| analytics::page_view(&req));
+                let res = srv.call(req);
+                async move {
+                    let res = res.await?;
+                    if let (Some(view), Some(site)) = (view, site) {
+                        if res.status().is_success() {
+                            if let Err(e) = analytics::record(&site.store, &view) {
+                                println!("Couldn't record page view: {}", e);
+                            }
+                        }
+                    }
+                    Ok(res)
+                }
+            })
+            .route(constants::ARTICLE_LIST_URI, web::get().to(articles::list))
+            .route(constants::ARCHIVE_URI, web::get().to(articles::archive))
+            .route(
+                &format!("{}/{{name}}", constants::ARTICLE_METADATA_URI),
+                web::get().to(articles::metadata),
+            )
+            .route(
+                &format!("/{}/{{name}}", constants::ARTICLES_PATH),
+                web::get().to(articles::content),
+            )
+            .route(
+                "/preview/articles/{name:.*}",
+                web::get().to(articles::preview),
+            )
+            .route(
+                &format!("{}/{{article}}", constants::REVISIONS_URI),
+                web::get().to(revisions::list),
+            )
+            .route(
+                &format!("{}/{{article}}/diff", constants::REVISIONS_URI),
+                web::get().to(revisions::diff),
+            )
+            .route(constants::SITE_URI, web::get().to(sites::info))
+            .route(constants::THEME_URI, web::get().to(sites::theme))
+            .route(constants::ABOUT_URI, web::get().to(sites::about))
+            .route(constants::AUTHORS_URI, web::get().to(authors::list))
+            .route(
+                &format!("{}/{{id}}", constants::AUTHORS_URI),
+                web::get().to(authors::author),
             )
-            .route("/preview/articles/{filename:.*}", web::get().to(preview))
-            .service(afs::Files::new("/", constants::STATIC_URL).index_file("index.html"))
+            .configure(|cfg| feature_routes(sites.clone(), cfg))
+            .configure(|cfg| article_files(sites.clone(), cfg))
+            .service(afs::Files::new("/", &static_dir).index_file("index.html"))
     })
     .bind(binding_ip)?
     .run()
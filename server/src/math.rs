@@ -0,0 +1,268 @@
+use latex2mathml::{latex_to_mathml, DisplayStyle};
+use pulldown_cmark as pc;
+use shared::markdown::{self, Extensions};
+use std::ops::Range;
+
+// Turns `$...$` and `$$...$$` into MathML before the article reaches the client, so readers don't
+// need any JS to see the formulas. Only text is looked at, code(fenced, indented or inline), links
+// and HTML are left untouched.
+
+// Markdown would still parse the text between the inline MathML tags, so anything it could
+// interpret is written as an entity
+fn escape_markdown(html: &str) -> String {
+    let mut escaped = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            '*' | '_' | '\\' | '`' | '[' | ']' | '~' | '$' if !in_tag => {
+                escaped.push_str(&format!("&#{};", c as u32));
+                continue;
+            }
+            _ => {}
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn render(latex: &str, display: bool) -> String {
+    let style = if display {
+        DisplayStyle::Block
+    } else {
+        DisplayStyle::Inline
+    };
+
+    match latex_to_mathml(latex, style) {
+        // Inline HTML as well, so it can be in lists and quotes, the MathML already displays as a
+        // block
+        Ok(mathml) if display => format!(
+            "<span class=\"math-display\">{}</span>",
+            escape_markdown(&mathml)
+        ),
+        Ok(mathml) => format!("<span class=\"math\">{}</span>", escape_markdown(&mathml)),
+        Err(e) => {
+            println!("Couldn't render math `{}`: {}", latex, e);
+            let delimiter = if display { "$$" } else { "$" };
+            format!("`{}{}{}`", delimiter, latex, delimiter)
+        }
+    }
+}
+
+// Inline math follows pandoc's rules so prices like "$5 and $10" aren't taken as math: the opening
+// `$` can't be followed by a space, the closing one can't be preceded by a space nor followed by a
+// digit, and it can't span paragraphs.
+fn inline_math_end(text: &str, start: usize) -> Option<usize> {
+    let content = &text[start + 1..];
+    if content.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let paragraph_end = content.find("\n\n").unwrap_or(content.len());
+    let mut search = 0;
+    while let Some(offset) = content[search..paragraph_end].find('$') {
+        let end = search + offset;
+        let escaped = content[..end].ends_with('\\');
+        let space_before = content[..end].ends_with(char::is_whitespace);
+        let digit_after = content[end + 1..].starts_with(|c: char| c.is_ascii_digit());
+        if end > 0 && !escaped && !space_before && !digit_after {
+            return Some(start + 1 + end);
+        }
+        search = end + 1;
+    }
+    None
+}
+
+fn render_text(text: &str, output: &mut String) {
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        if rest.starts_with("\\$") {
+            output.push_str("\\$");
+            i += 2;
+        } else if let Some(after) = rest.strip_prefix("$$") {
+            match after.find("$$") {
+                Some(end) => {
+                    output.push_str(&render(after[..end].trim(), true));
+                    i += end + 4;
+                }
+                None => {
+                    output.push_str("$$");
+                    i += 2;
+                }
+            }
+        } else if rest.starts_with('$') {
+            match inline_math_end(text, i) {
+                Some(end) => {
+                    output.push_str(&render(&text[i + 1..end], false));
+                    i = end + 1;
+                }
+                None => {
+                    output.push('$');
+                    i += 1;
+                }
+            }
+        } else {
+            let c = rest.chars().next().unwrap();
+            output.push(c);
+            i += c.len_utf8();
+        }
+    }
+}
+
+// Emphasis doesn't end a run of text, math like `$a*b$ and $c*d$` can look like it
+fn is_emphasis(tag: &pc::Tag) -> bool {
+    matches!(
+        tag,
+        pc::Tag::Emphasis | pc::Tag::Strong | pc::Tag::Strikethrough
+    )
+}
+
+// The runs of text of each block, the only places math can be. Anything else(code, links, images,
+// HTML...) ends a run, so math is never looked for in URLs or attributes and can't span them.
+fn text_runs(markdown: &str, extensions: Extensions) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut run: Option<Range<usize>> = None;
+    // Inside a link, an image or a code block
+    let mut skipped = 0;
+    for (event, range) in markdown::parser(markdown, extensions).into_offset_iter() {
+        match event {
+            pc::Event::Text(_) | pc::Event::SoftBreak | pc::Event::HardBreak if skipped == 0 => {
+                run = Some(run.map_or(range.clone(), |run| run.start..range.end));
+                continue;
+            }
+            pc::Event::Start(tag) | pc::Event::End(tag) if skipped == 0 && is_emphasis(&tag) => {
+                continue;
+            }
+            pc::Event::Start(pc::Tag::Link(..))
+            | pc::Event::Start(pc::Tag::Image(..))
+            | pc::Event::Start(pc::Tag::CodeBlock(_)) => skipped += 1,
+            pc::Event::End(pc::Tag::Link(..))
+            | pc::Event::End(pc::Tag::Image(..))
+            | pc::Event::End(pc::Tag::CodeBlock(_)) => skipped -= 1,
+            _ => {}
+        }
+        runs.extend(run.take());
+    }
+    runs.extend(run);
+    runs
+}
+
+pub fn render_math(markdown: &str, extensions: Extensions) -> String {
+    let mut output = String::with_capacity(markdown.len());
+    let mut copied = 0;
+    for run in text_runs(markdown, extensions) {
+        output.push_str(&markdown[copied..run.start]);
+        render_text(&markdown[run.clone()], &mut output);
+        copied = run.end;
+    }
+    output.push_str(&markdown[copied..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_items(markdown: &str) -> usize {
+        pc::Parser::new(markdown)
+            .filter(|event| matches!(event, pc::Event::Start(pc::Tag::Item)))
+            .count()
+    }
+
+    #[test]
+    fn code_is_left_alone() {
+        for code in &[
+            "```\n$x$ and $$y$$\n```\n",
+            "~~~latex\n$x$\n~~~\n",
+            "    let price = \"$x$\";\n",
+            "- item\n\n  ```\n  $x$\n  ```\n",
+            "`$x$` and ``$`y`$``\n",
+        ] {
+            assert_eq!(render_math(code, Extensions::default()), *code);
+        }
+
+        let rendered = render_math("Some $x$\n\n    $y$\n\nand $z$\n", Extensions::default());
+        assert!(rendered.contains("\n\n    $y$\n\n"), "{}", rendered);
+        assert_eq!(rendered.matches("<span class=\"math\">").count(), 2);
+    }
+
+    #[test]
+    fn only_math_is_rendered() {
+        for (markdown, math) in &[
+            ("$x$", 1),
+            ("$$x = 1$$", 1),
+            ("It costs $5 and $10", 0),
+            ("A $ sign and $ another", 0),
+            ("\\$x\\$", 0),
+            ("$x\n\ny$", 0),
+            ("$x$, $y$ and $$z$$", 3),
+            ("$a*b$ and $c*d$", 2),
+        ] {
+            let rendered = render_math(markdown, Extensions::default());
+            assert_eq!(rendered.matches("<math").count(), *math, "{}", rendered);
+        }
+    }
+
+    #[test]
+    fn links_and_html_are_left_alone() {
+        for markdown in &[
+            "[x](https://a/?q=$a$)\n",
+            "<https://a/?q=$a$>\n",
+            "![$x$](img.png)\n",
+            "<a title=\"$x$ and $y$\">link</a>\n",
+            "<div title=\"$$x$$\">\n",
+        ] {
+            assert_eq!(render_math(markdown, Extensions::default()), *markdown);
+        }
+
+        let rendered = render_math("$x$ [link](https://a/?q=$a$) $y$", Extensions::default());
+        assert!(rendered.contains("](https://a/?q=$a$)"), "{}", rendered);
+        assert_eq!(rendered.matches("<math").count(), 2, "{}", rendered);
+    }
+
+    #[test]
+    fn display_math_ends_with_its_text() {
+        for (markdown, math) in &[
+            ("$$x\n\ny$$", 0),
+            ("$$x `code` y$$", 0),
+            ("$$x [link](y) y$$", 0),
+            ("$$x\n\nand $y$", 1),
+            ("$$\nx^2\n$$", 1),
+        ] {
+            let rendered = render_math(markdown, Extensions::default());
+            assert_eq!(rendered.matches("<math").count(), *math, "{}", rendered);
+        }
+
+        let rendered = render_math("$$x\n\nand $y$", Extensions::default());
+        assert!(rendered.starts_with("$$x\n\nand "), "{}", rendered);
+    }
+
+    #[test]
+    fn display_math_stays_where_it_is() {
+        let list = "- first $$x^2$$\n- second\n";
+        let rendered = render_math(list, Extensions::default());
+        assert!(
+            rendered.contains("<span class=\"math-display\"><math"),
+            "{}",
+            rendered
+        );
+        assert!(!rendered.contains("\n\n"), "{}", rendered);
+        assert_eq!(list_items(&rendered), 2);
+
+        let quote = render_math("> $$a_1 * b_2$$\n> quoted\n", Extensions::default());
+        let events: Vec<_> = pc::Parser::new(&quote).collect();
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, pc::Event::Start(pc::Tag::BlockQuote)))
+                .count(),
+            1
+        );
+        // The subscripts and products aren't emphasis
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, pc::Event::Start(pc::Tag::Emphasis))));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::errors;
+use crate::rate_limit::{ClientAddress, RateLimiter};
+use actix_web::{web, HttpResponse, Result};
+use awc::Client;
+use serde_json::json;
+use shared::playground::{Execution, ExecutionOutput};
+use std::time::Duration;
+use url::Url;
+
+const MAX_CODE_LENGTH: usize = 64 * 1024;
+// Compiling can take a while, awc's default 5 seconds isn't enough
+const EXECUTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Snippets are run through any endpoint compatible with the playground's `/execute`(the real one
+// by default), proxied so it can be swapped for a local one and so we can rate limit it.
+pub struct Playground {
+    pub url: Url,
+    pub rate_limiter: RateLimiter,
+}
+
+pub async fn execute(
+    client: ClientAddress,
+    playground: web::Data<Playground>,
+    execution: web::Json<Execution>,
+) -> Result<HttpResponse> {
+    if !playground.rate_limiter.check(&client.0) {
+        return Err(errors::too_many_requests("Too many runs, try again later"));
+    }
+
+    if execution.code.len() > MAX_CODE_LENGTH {
+        return Err(errors::bad_request("Code too long"));
+    }
+
+    let mut response = Client::builder()
+        .timeout(EXECUTION_TIMEOUT)
+        .finish()
+        .post(playground.url.as_str())
+        .send_json(&json!({
+            "channel": "stable",
+            "mode": "debug",
+            "edition": "2018",
+            "crateType": "bin",
+            "tests": false,
+            "backtrace": false,
+            "code": execution.code,
+        }))
+        .await
+        .map_err(|e| errors::bad_gateway(e.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(errors::bad_gateway(format!(
+            "Playground answered {}",
+            response.status()
+        )));
+    }
+
+    let output: ExecutionOutput = response
+        .json()
+        .await
+        .map_err(|e| errors::bad_gateway(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(output))
+}
+
+// Takes `execute`'s place when the playground is turned off, runnable snippets are still rendered
+// so they get an answer instead of a bare 404
+pub async fn disabled() -> Result<HttpResponse> {
+    Err(errors::forbidden("The playground is disabled"))
+}
@@ -0,0 +1,124 @@
+use actix_web::dev::Payload;
+use actix_web::{web, FromRequest, HttpRequest};
+use std::collections::{HashMap, VecDeque};
+use std::future::{ready, Ready};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Sliding window limiter keyed by client(usually its IP).
+// Kept in memory, a restart resetting the limits is fine.
+pub struct RateLimiter {
+    max_requests: usize,
+    window: Duration,
+    requests: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: usize, window: Duration) -> RateLimiter {
+        RateLimiter {
+            max_requests,
+            window,
+            requests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Records the attempt and returns whether it's allowed
+    pub fn check(&self, client: &str) -> bool {
+        let now = Instant::now();
+        let mut requests = self.requests.lock().unwrap();
+        requests.retain(|_, attempts| {
+            attempts
+                .back()
+                .is_some_and(|last| now.duration_since(*last) < self.window)
+        });
+
+        let attempts = requests.entry(client.to_string()).or_default();
+        while attempts
+            .front()
+            .is_some_and(|first| now.duration_since(*first) >= self.window)
+        {
+            attempts.pop_front();
+        }
+
+        if attempts.len() >= self.max_requests {
+            return false;
+        }
+
+        attempts.push_back(now);
+        true
+    }
+}
+
+// Whether the `X-Forwarded-For` the proxy in front of the server appends to can be trusted, see
+// `Config::behind_proxy`
+#[derive(Clone, Copy)]
+pub struct BehindProxy(pub bool);
+
+// Extractor for the address limits are keyed on. It's the one the request came from, unless the
+// server is behind a proxy, then it's the last one in `X-Forwarded-For`, which is the one the proxy
+// added. Those before it come from the client, who can make them up.
+pub struct ClientAddress(pub String);
+
+fn client_address(req: &HttpRequest) -> String {
+    let behind_proxy = matches!(
+        req.app_data::<web::Data<BehindProxy>>(),
+        Some(behind_proxy) if behind_proxy.0
+    );
+    let forwarded = req
+        .headers()
+        .get_all("X-Forwarded-For")
+        .last()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .map(str::trim)
+        .filter(|address| behind_proxy && !address.is_empty());
+    match forwarded {
+        Some(address) => address.to_string(),
+        None => req
+            .peer_addr()
+            .map(|addr| addr.ip().to_string())
+            .unwrap_or_else(|| String::from("unknown")),
+    }
+}
+
+impl FromRequest for ClientAddress {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(ClientAddress(client_address(req))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn forwarded_addresses_are_only_trusted_behind_a_proxy() {
+        for (behind_proxy, forwarded_for, address) in &[
+            (false, None, "192.0.2.1"),
+            (false, Some("198.51.100.7"), "192.0.2.1"),
+            (true, None, "192.0.2.1"),
+            (true, Some("198.51.100.7"), "198.51.100.7"),
+            // Everything but the last one comes from the client
+            (true, Some("203.0.113.9, 198.51.100.7"), "198.51.100.7"),
+        ] {
+            let mut request = TestRequest::default()
+                .peer_addr("192.0.2.1:4242".parse().unwrap())
+                .data(BehindProxy(*behind_proxy));
+            if let Some(forwarded_for) = forwarded_for {
+                request = request.header("X-Forwarded-For", *forwarded_for);
+            }
+            assert_eq!(
+                client_address(&request.to_http_request()),
+                *address,
+                "{} behind a proxy: {}",
+                forwarded_for.unwrap_or("no X-Forwarded-For"),
+                behind_proxy
+            );
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::articles::ArticleIndex;
+use crate::errors;
+use crate::sites::Site;
+use crate::store::{Store, StoreResult};
+use actix_web::{web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use shared::revisions::{
+    ChangeTag, Diff, DiffMode, DiffSegment, Revision, RevisionDiff, Revisions,
+};
+use similar::{ChangeTag as SimilarTag, TextDiff};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+const REVISIONS_TREE: &str = "revisions";
+const DIFF_CONTEXT_LINES: usize = 3;
+
+#[derive(Deserialize, Serialize)]
+struct StoredRevision {
+    revision: Revision,
+    content: String,
+}
+
+#[derive(Deserialize)]
+pub struct DiffQuery {
+    from: Option<u32>,
+    to: Option<u32>,
+    #[serde(default)]
+    mode: DiffMode,
+}
+
+// Zero padded so that sled's lexicographic ordering matches the revision order
+fn revision_key(article: &str, id: u32) -> String {
+    format!("{}/{:010}", article, id)
+}
+
+fn article_prefix(article: &str) -> String {
+    format!("{}/", article)
+}
+
+fn modified_timestamp(path: &Path) -> io::Result<u64> {
+    let modified = fs::metadata(path)?.modified()?;
+    Ok(modified
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default())
+}
+
+// Snapshots every article whose content differs from its latest stored revision and returns
+// those articles. Edits are made directly to the files, so this runs on startup to pick them up,
+// and only then: several edits between two restarts are a single revision. The files don't say who
+// changed them or when, so every revision is credited to the site's `revision_author` and dated
+// with the file's modification time.
+pub fn record_revisions(
+    store: &Store,
+    index: &ArticleIndex,
+    articles_dir: &Path,
+    author: &str,
+) -> io::Result<Vec<String>> {
+    let mut changed = Vec::new();
+    for article in index.articles() {
+        let content = &article.content;
+        let article = &article.metadata.name;
+        let latest: Option<StoredRevision> = store
+            .last_with_prefix(REVISIONS_TREE, &article_prefix(article))
+            .map_err(io::Error::other)?;
+
+        if latest.as_ref().map(|r| &r.content) == Some(content) {
+            continue;
+        }
+
+        let revision = Revision {
+            id: latest.map(|r| r.revision.id + 1).unwrap_or(1),
+            timestamp: modified_timestamp(&articles_dir.join(article))?,
+            author: author.to_string(),
+        };
+        store
+            .insert(
+                REVISIONS_TREE,
+                &revision_key(article, revision.id),
+                &StoredRevision {
+                    revision,
+                    content: content.clone(),
+                },
+            )
+            .map_err(io::Error::other)?;
+        changed.push(article.clone());
+    }
+
+    Ok(changed)
+}
+
+// When every article's first revision was recorded
+pub fn first_revisions(store: &Store, index: &ArticleIndex) -> io::Result<HashMap<String, u64>> {
+    let mut first_revisions = HashMap::new();
+    for article in index.articles() {
+        let name = &article.metadata.name;
+        let first: Option<StoredRevision> = store
+            .first_with_prefix(REVISIONS_TREE, &article_prefix(name))
+            .map_err(io::Error::other)?;
+        if let Some(first) = first {
+            first_revisions.insert(name.clone(), first.revision.timestamp);
+        }
+    }
+    Ok(first_revisions)
+}
+
+fn stored_revisions(store: &Store, article: &str) -> StoreResult<Vec<StoredRevision>> {
+    Ok(store
+        .scan_prefix(REVISIONS_TREE, &article_prefix(article))?
+        .into_iter()
+        .map(|(_, revision)| revision)
+        .collect())
+}
+
+fn known_article(site: &Site, article: &str) -> Result<String> {
+    match site.index.resolve(article) {
+        Some(article) => Ok(article.metadata.name.clone()),
+        None => Err(errors::not_found("Unknown article")),
+    }
+}
+
+pub async fn list(site: Site, article: web::Path<String>) -> Result<HttpResponse> {
+    let article = known_article(&site, &article)?;
+    let revisions = stored_revisions(&site.store, &article)?
+        .into_iter()
+        .map(|r| r.revision)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(Revisions { article, revisions }))
+}
+
+fn diff_segments(old: &str, new: &str) -> Vec<DiffSegment> {
+    TextDiff::from_words(old, new)
+        .iter_all_changes()
+        .map(|change| DiffSegment {
+            tag: match change.tag() {
+                SimilarTag::Equal => ChangeTag::Equal,
+                SimilarTag::Insert => ChangeTag::Insert,
+                SimilarTag::Delete => ChangeTag::Delete,
+            },
+            value: change.value().to_string(),
+        })
+        .collect()
+}
+
+// Without explicit ids this diffs the two latest revisions, which is what the "view changes"
+// link on an article wants.
+pub async fn diff(
+    site: Site,
+    article: web::Path<String>,
+    query: web::Query<DiffQuery>,
+) -> Result<HttpResponse> {
+    let article = known_article(&site, &article)?;
+    let revisions = stored_revisions(&site.store, &article)?;
+    let latest = match revisions.last() {
+        Some(latest) => latest.revision.id,
+        None => return Err(errors::not_found("No revisions for this article")),
+    };
+
+    let to_id = query.to.unwrap_or(latest);
+    let from_id = query.from.unwrap_or_else(|| to_id.saturating_sub(1).max(1));
+    let find = |id: u32| {
+        revisions
+            .iter()
+            .find(|r| r.revision.id == id)
+            .ok_or_else(|| errors::not_found(format!("Unknown revision {}", id)))
+    };
+    let from = find(from_id)?;
+    let to = find(to_id)?;
+
+    let diff = match query.mode {
+        DiffMode::Unified => Diff::Unified(
+            TextDiff::from_lines(&from.content, &to.content)
+                .unified_diff()
+                .context_radius(DIFF_CONTEXT_LINES)
+                .header(
+                    &revision_key(&article, from_id),
+                    &revision_key(&article, to_id),
+                )
+                .to_string(),
+        ),
+        DiffMode::Words => Diff::Words(diff_segments(&from.content, &to.content)),
+    };
+
+    Ok(HttpResponse::Ok().json(RevisionDiff {
+        article,
+        from: from.revision.clone(),
+        to: to.revision.clone(),
+        diff,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{SiteConfig, MAIN_SITE_ID};
+    use crate::sites::{SiteState, Sites};
+    use crate::temp_dir::TempDir;
+    use actix_web::{test, App};
+
+    #[actix_rt::test]
+    async fn only_articles_in_the_index_have_revisions() {
+        let articles = TempDir::with_files(&[("1_first.md", "# First")]);
+        let config = SiteConfig::for_tests(MAIN_SITE_ID, &[], articles.path().to_path_buf());
+        let (site, changed) = SiteState::load(config, &Store::temporary().unwrap()).unwrap();
+        assert_eq!(changed, vec!["1_first.md"]);
+        let mut app = test::init_service(
+            App::new()
+                .data(Sites::new(site, Vec::new()))
+                .route("/revisions/{article}", web::get().to(list))
+                .route("/revisions/{article}/diff", web::get().to(diff)),
+        )
+        .await;
+
+        for (uri, status) in &[
+            ("/revisions/1_first.md", 200),
+            ("/revisions/1_first.md/diff", 200),
+            ("/revisions/unknown.md", 404),
+            ("/revisions/unknown.md/diff", 404),
+            ("/revisions/..%2F1_first.md", 404),
+        ] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+            let response = test::call_service(&mut app, request).await.status();
+            assert_eq!(response, *status, "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use shared::markdown::{self, Extensions};
+use std::collections::HashMap;
+
+// Shortcodes let articles use components without writing raw HTML:
+//
+//     {{< figure src="articles/img/ferris.svg" caption="Ferris" class="portrait" >}}
+//     {{< note warning >}} Markdown **inside** {{< /note >}}
+//
+// Each name maps to a handler in the registry. Shortcodes inside code are left alone so articles
+// can show them, as are unknown or broken ones, which are only logged.
+
+const OPEN: &str = "{{<";
+const CLOSE: &str = ">}}";
+
+#[derive(Default)]
+pub struct Arguments {
+    positional: Vec<String>,
+    named: HashMap<String, String>,
+}
+
+impl Arguments {
+    // `key=value` or `key="quoted value"` are named, anything else is positional
+    fn parse(text: &str) -> Result<Arguments, String> {
+        let mut arguments = Arguments::default();
+        let mut rest = text.trim_start();
+        while !rest.is_empty() {
+            let word_end = rest
+                .find(|c: char| c.is_whitespace() || c == '=' || c == '"')
+                .unwrap_or(rest.len());
+            let (name, value) = match rest[word_end..].strip_prefix('=') {
+                Some(value) => (Some(&rest[..word_end]), value),
+                None => (None, rest),
+            };
+
+            let (value, after) = match value.strip_prefix('"') {
+                Some(quoted) => {
+                    let end = quoted
+                        .find('"')
+                        .ok_or_else(|| format!("Unclosed quote in `{}`", text))?;
+                    (&quoted[..end], &quoted[end + 1..])
+                }
+                None => {
+                    let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                    (&value[..end], &value[end..])
+                }
+            };
+
+            match name {
+                Some(name) => arguments.named.insert(name.to_string(), value.to_string()),
+                None => {
+                    arguments.positional.push(value.to_string());
+                    None
+                }
+            };
+            rest = after.trim_start();
+        }
+        Ok(arguments)
+    }
+
+    // Arguments can be given by name or by position
+    pub fn get(&self, name: &str, position: usize) -> Option<&str> {
+        self.named
+            .get(name)
+            .or_else(|| self.positional.get(position))
+            .map(String::as_str)
+    }
+
+    pub fn require(&self, name: &str, position: usize) -> Result<&str, String> {
+        self.get(name, position)
+            .ok_or_else(|| format!("Missing `{}`", name))
+    }
+}
+
+// Returns the HTML replacing the shortcode, paired shortcodes also get their (already processed)
+// inner markdown
+pub type Handler = fn(&Arguments, Option<&str>) -> Result<String, String>;
+
+struct Shortcode {
+    handler: Handler,
+    paired: bool,
+}
+
+pub struct ShortcodeRegistry {
+    shortcodes: HashMap<&'static str, Shortcode>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Classes match the styles images get through their URL fragment(`#portrait`, `#article`...)
+fn figure(arguments: &Arguments, _: Option<&str>) -> Result<String, String> {
+    let src = arguments.require("src", 0)?;
+    let caption = arguments.get("caption", 1);
+    let alt = arguments.get("alt", 2).or(caption).unwrap_or("");
+    let class = arguments.get("class", 3).unwrap_or("article");
+
+    let caption = caption.map_or(String::new(), |caption| {
+        format!("<figcaption>{}</figcaption>", escape(caption))
+    });
+    Ok(format!(
+        "<figure class=\"figure {}\"><img src=\"{}\" alt=\"{}\" />{}</figure>",
+        escape(class),
+        escape(src),
+        escape(alt),
+        caption
+    ))
+}
+
+// The blank lines let markdown parse the content between the HTML tags
+fn note(arguments: &Arguments, content: Option<&str>) -> Result<String, String> {
+    let kind = arguments.get("type", 0).unwrap_or("info");
+    Ok(format!(
+        "<div class=\"note note-{}\">\n\n{}\n\n</div>",
+        escape(kind),
+        content.unwrap_or("").trim()
+    ))
+}
+
+fn youtube(arguments: &Arguments, _: Option<&str>) -> Result<String, String> {
+    let id = arguments.require("id", 0)?;
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid video id `{}`", id));
+    }
+
+    Ok(format!(
+        "<div class=\"video\"><iframe src=\"https://www.youtube-nocookie.com/embed/{}\" \
+        title=\"YouTube video\" loading=\"lazy\" allowfullscreen></iframe></div>",
+        id
+    ))
+}
+
+// Gists are embedded with a script, which wouldn't run once the article is set as inner HTML, so
+// it's loaded in its own frame
+fn gist(arguments: &Arguments, _: Option<&str>) -> Result<String, String> {
+    let user = arguments.require("user", 0)?;
+    let id = arguments.require("id", 1)?;
+    let valid = |s: &str| {
+        s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    };
+    if !valid(user) || !valid(id) {
+        return Err(format!("Invalid gist `{}/{}`", user, id));
+    }
+
+    let file = arguments
+        .get("file", 2)
+        .map_or(String::new(), |file| format!("?file={}", escape(file)));
+    let script = format!(
+        "<base target='_parent'><script src='https://gist.github.com/{}/{}.js{}'></script>",
+        user, id, file
+    );
+    Ok(format!(
+        "<div class=\"gist\"><iframe srcdoc=\"{}\" title=\"Gist {}\" loading=\"lazy\"></iframe>\
+        </div>",
+        escape(&script),
+        id
+    ))
+}
+
+impl Default for ShortcodeRegistry {
+    fn default() -> Self {
+        let mut registry = ShortcodeRegistry {
+            shortcodes: HashMap::new(),
+        };
+        registry.register("figure", figure, false);
+        registry.register("note", note, true);
+        registry.register("youtube", youtube, false);
+        registry.register("gist", gist, false);
+        registry
+    }
+}
+
+// A `{{< ... >}}` found in the article, `start` and `end` are byte offsets around it
+struct Tag<'a> {
+    start: usize,
+    end: usize,
+    inner: &'a str,
+}
+
+impl<'a> Tag<'a> {
+    fn name(&self) -> &'a str {
+        self.inner.split_whitespace().next().unwrap_or("")
+    }
+
+    fn is_closing(&self, name: &str) -> bool {
+        self.inner.strip_prefix('/').map(str::trim) == Some(name)
+    }
+}
+
+// Every shortcode tag outside of code blocks and code spans
+fn tags(text: &str, extensions: Extensions) -> Vec<Tag<'_>> {
+    let code = markdown::code_ranges(text, extensions);
+    let mut tags = Vec::new();
+    let mut i = 0;
+    while let Some(start) = text[i..].find(OPEN).map(|n| i + n) {
+        if let Some(range) = code.iter().find(|range| range.contains(&start)) {
+            i = range.end;
+            continue;
+        }
+
+        let end = match text[start..].find(CLOSE) {
+            Some(end) => start + end + CLOSE.len(),
+            None => break,
+        };
+        tags.push(Tag {
+            start,
+            end,
+            inner: text[start + OPEN.len()..end - CLOSE.len()].trim(),
+        });
+        i = end;
+    }
+    tags
+}
+
+impl ShortcodeRegistry {
+    pub fn register(&mut self, name: &'static str, handler: Handler, paired: bool) {
+        self.shortcodes.insert(name, Shortcode { handler, paired });
+    }
+
+    // Index of the tag closing the one at `open`, shortcodes with the same name can be nested
+    fn closing(tags: &[Tag], open: usize) -> Option<usize> {
+        let name = tags[open].name();
+        let mut depth = 0;
+        for (i, tag) in tags.iter().enumerate().skip(open + 1) {
+            if tag.name() == name {
+                depth += 1;
+            } else if tag.is_closing(name) {
+                if depth == 0 {
+                    return Some(i);
+                }
+                depth -= 1;
+            }
+        }
+        None
+    }
+
+    // The rendered shortcode and the index of the last tag it used
+    fn render_tag(
+        &self,
+        text: &str,
+        tags: &[Tag],
+        i: usize,
+        extensions: Extensions,
+    ) -> Result<(String, usize), String> {
+        let tag = &tags[i];
+        let shortcode = self
+            .shortcodes
+            .get(tag.name())
+            .ok_or_else(|| format!("Unknown shortcode `{}`", tag.name()))?;
+        let arguments = Arguments::parse(&tag.inner[tag.name().len()..])?;
+
+        if !shortcode.paired {
+            return Ok(((shortcode.handler)(&arguments, None)?, i));
+        }
+
+        let close = ShortcodeRegistry::closing(tags, i)
+            .ok_or_else(|| format!("`{}` is never closed", tag.name()))?;
+        let content = self.render_shortcodes(&text[tag.end..tags[close].start], extensions);
+        Ok(((shortcode.handler)(&arguments, Some(&content))?, close))
+    }
+
+    // Whether `offset` falls between the tags of a paired shortcode, where the content can't be
+    // cut
+    pub fn inside_shortcode(&self, content: &str, offset: usize, extensions: Extensions) -> bool {
+        let tags = tags(content, extensions);
+        tags.iter().enumerate().any(|(i, tag)| {
+            let paired = self
+                .shortcodes
+                .get(tag.name())
+                .is_some_and(|shortcode| shortcode.paired);
+            paired
+                && tag.start < offset
+                && ShortcodeRegistry::closing(&tags, i)
+                    .is_some_and(|close| tags[close].end > offset)
+        })
+    }
+
+    pub fn render_shortcodes(&self, content: &str, extensions: Extensions) -> String {
+        let tags = tags(content, extensions);
+        let mut output = String::with_capacity(content.len());
+        let mut copied = 0;
+        let mut i = 0;
+        while i < tags.len() {
+            match self.render_tag(content, &tags, i, extensions) {
+                Ok((html, last)) => {
+                    output.push_str(&content[copied..tags[i].start]);
+                    output.push_str(&html);
+                    copied = tags[last].end;
+                    i = last + 1;
+                }
+                Err(e) => {
+                    println!("Couldn't render shortcode `{}`: {}", tags[i].inner, e);
+                    i += 1;
+                }
+            }
+        }
+        output.push_str(&content[copied..]);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcodes_in_code_are_left_alone() {
+        let registry = ShortcodeRegistry::default();
+        let cases = [
+            "```\n{{< youtube abc >}}\n```\n",
+            "````md\n```\n{{< youtube abc >}}\n```\n````\n",
+            "Text\n\n    {{< youtube abc >}}\n",
+            "- Item\n\n  ```\n  {{< youtube abc >}}\n  ```\n",
+            "Inline `{{< youtube abc >}}` code\n",
+            "Double ``a ` {{< youtube abc >}}`` span\n",
+        ];
+        for &content in &cases {
+            let rendered = registry.render_shortcodes(content, Extensions::default());
+            assert_eq!(rendered, content, "{}", content);
+        }
+
+        let rendered = registry.render_shortcodes(
+            "`code` {{< youtube abc >}}\n\n    {{< youtube abc >}}\n",
+            Extensions::default(),
+        );
+        assert_eq!(
+            rendered.matches("youtube-nocookie.com/embed/abc").count(),
+            1,
+            "{}",
+            rendered
+        );
+    }
+}
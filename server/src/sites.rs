@@ -0,0 +1,246 @@
+use crate::articles::ArticleIndex;
+use crate::authors::AuthorIndex;
+use crate::config::{SiteConfig, MAIN_SITE_ID};
+use crate::errors;
+use crate::revisions;
+use crate::store::Store;
+use actix_web::dev::{Payload, RequestHead};
+use actix_web::guard::{self, Guard};
+use actix_web::http::header;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse, Result};
+use shared::constants;
+use shared::site::{SiteFeatures, SiteInfo};
+use std::collections::HashMap;
+use std::fs;
+use std::future::{ready, Ready};
+use std::io;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// A blog served by the process, everything it needs is loaded on startup
+pub struct SiteState {
+    pub config: SiteConfig,
+    pub index: ArticleIndex,
+    pub authors: AuthorIndex,
+    // The process' store, with the site's own trees
+    pub store: Store,
+    theme: Option<String>,
+    about: Option<String>,
+}
+
+impl SiteState {
+    // Loads the articles and records their revisions, returning the names of those that changed
+    pub fn load(config: SiteConfig, store: &Store) -> io::Result<(SiteState, Vec<String>)> {
+        let store = if config.id == MAIN_SITE_ID {
+            store.clone()
+        } else {
+            store.for_site(&config.id)
+        };
+        let mut index = ArticleIndex::load(&config.articles_dir, config.extensions)?;
+        let changed_articles = revisions::record_revisions(
+            &store,
+            &index,
+            &config.articles_dir,
+            &config.revision_author,
+        )?;
+        index.set_publication_dates(&revisions::first_revisions(&store, &index)?);
+        let authors = match &config.authors_dir {
+            Some(dir) => AuthorIndex::load(dir)?,
+            None => AuthorIndex::default(),
+        };
+        index.set_authors(&authors);
+        index.set_previews(config.preview_lines);
+
+        let read = |path: &Option<PathBuf>| path.as_ref().map(fs::read_to_string).transpose();
+        let site = SiteState {
+            theme: read(&config.theme)?,
+            about: read(&config.about)?,
+            config,
+            index,
+            authors,
+            store,
+        };
+        Ok((site, changed_articles))
+    }
+}
+
+// Extractor for the site a request is for, any host no other site has gets the main one
+#[derive(Clone)]
+pub struct Site(Arc<SiteState>);
+
+impl Deref for Site {
+    type Target = SiteState;
+
+    fn deref(&self) -> &SiteState {
+        &self.0
+    }
+}
+
+impl FromRequest for Site {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(match req.app_data::<web::Data<Sites>>() {
+            Some(sites) => Ok(sites.for_request(req.head())),
+            None => Err(errors::internal("No sites registered")),
+        })
+    }
+}
+
+pub struct Sites {
+    // The main site first
+    sites: Vec<Site>,
+    by_host: HashMap<String, Site>,
+}
+
+// The `Host` header, or the authority of HTTP/2 requests, as written in the configuration
+fn request_host(head: &RequestHead) -> Option<String> {
+    let host = head
+        .headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .or_else(|| head.uri.host())?;
+    host.split(':').next().map(str::to_lowercase)
+}
+
+impl Sites {
+    pub fn new(main_site: SiteState, sites: Vec<SiteState>) -> Sites {
+        let sites: Vec<Site> = std::iter::once(main_site)
+            .chain(sites)
+            .map(|site| Site(Arc::new(site)))
+            .collect();
+        let by_host = sites
+            .iter()
+            .flat_map(|site| {
+                site.config
+                    .hosts
+                    .iter()
+                    .map(move |host| (host.clone(), site.clone()))
+            })
+            .collect();
+        Sites { sites, by_host }
+    }
+
+    pub fn all(&self) -> &[Site] {
+        &self.sites
+    }
+
+    pub fn for_request(&self, head: &RequestHead) -> Site {
+        request_host(head)
+            .and_then(|host| self.by_host.get(&host))
+            .unwrap_or(&self.sites[0])
+            .clone()
+    }
+}
+
+// Services behind it only exist for the sites passing `check`, for the others the request goes on
+// to the next matching service
+pub fn guard(sites: web::Data<Sites>, check: impl Fn(&SiteState) -> bool + 'static) -> impl Guard {
+    guard::fn_guard(move |head| check(&sites.for_request(head)))
+}
+
+// What the client needs from the site's configuration
+pub async fn info(site: Site) -> HttpResponse {
+    let config = &site.config;
+    HttpResponse::Ok().json(SiteInfo {
+        title: config.site_title.clone(),
+        url: config.site_url.to_string(),
+        features: SiteFeatures {
+            comments: config.features.comments,
+            webmentions: config.features.webmentions,
+            playground: config.features.playground,
+        },
+        extensions: config.extensions,
+        theme: site
+            .theme
+            .as_ref()
+            .map(|_| constants::THEME_URI.to_string()),
+        about: site
+            .about
+            .as_ref()
+            .map(|_| constants::ABOUT_URI.to_string()),
+    })
+}
+
+pub async fn theme(site: Site) -> Result<HttpResponse> {
+    match &site.theme {
+        Some(theme) => Ok(HttpResponse::Ok()
+            .content_type("text/css; charset=utf-8")
+            .body(theme.clone())),
+        None => Err(errors::not_found("The site has no theme")),
+    }
+}
+
+pub async fn about(site: Site) -> Result<HttpResponse> {
+    match &site.about {
+        Some(about) => Ok(HttpResponse::Ok()
+            .content_type("text/markdown; charset=utf-8")
+            .body(about.clone())),
+        None => Err(errors::not_found("The site has no about page")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::articles;
+    use crate::temp_dir::TempDir;
+    use actix_web::{test, App};
+    use shared::article_list::Articles;
+
+    fn articles_dir(article: &str) -> TempDir {
+        TempDir::with_files(&[(article, "# Article")])
+    }
+
+    #[actix_rt::test]
+    async fn requests_are_answered_by_the_site_of_their_host() {
+        let store = Store::temporary().unwrap();
+        let (main_dir, other_dir) = (articles_dir("main.md"), articles_dir("other.md"));
+        let main_config = SiteConfig::for_tests(MAIN_SITE_ID, &[], main_dir.path().to_path_buf());
+        let mut other_config =
+            SiteConfig::for_tests("other", &["other.example"], other_dir.path().to_path_buf());
+        other_config.features.comments = false;
+        let (main_site, _) = SiteState::load(main_config, &store).unwrap();
+        let (other_site, _) = SiteState::load(other_config, &store).unwrap();
+        let sites = web::Data::new(Sites::new(main_site, vec![other_site]));
+
+        let comments = guard(sites.clone(), |site| site.config.features.comments);
+        let mut app = test::init_service(
+            App::new()
+                .app_data(sites.clone())
+                .route(constants::ARTICLE_LIST_URI, web::get().to(articles::list))
+                .service(
+                    web::resource("/comments")
+                        .guard(comments)
+                        .route(web::get().to(HttpResponse::Ok)),
+                ),
+        )
+        .await;
+
+        for (host, article) in &[
+            ("other.example", "other.md"),
+            ("OTHER.example:8080", "other.md"),
+            ("main.example", "main.md"),
+        ] {
+            let request = test::TestRequest::get()
+                .uri(constants::ARTICLE_LIST_URI)
+                .header(header::HOST, *host)
+                .to_request();
+            let list: Articles = test::read_response_json(&mut app, request).await;
+            let names: Vec<_> = list.articles.iter().map(|a| a.name.as_str()).collect();
+            assert_eq!(names, vec![*article], "listed for {}", host);
+        }
+
+        for (host, status) in &[("main.example", 200), ("other.example", 404)] {
+            let request = test::TestRequest::get()
+                .uri("/comments")
+                .header(header::HOST, *host)
+                .to_request();
+            let response = test::call_service(&mut app, request).await;
+            assert_eq!(response.status(), *status, "comments of {}", host);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::errors;
+use actix_web::{HttpResponse, ResponseError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt;
+use std::path::Path;
+
+// Embedded KV store shared by everything that needs to persist data across restarts.
+// Values are stored as JSON so they can be inspected by hand if needed.
+// Every site has its own trees within the same database, prefixed by the site's id.
+#[derive(Clone)]
+pub struct Store {
+    db: sled::Db,
+    // Empty for the main site, so its trees keep the names they had before there were more sites
+    namespace: String,
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Db(sled::Error),
+    Serialization(serde_json::Error),
+}
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Db(e) => write!(f, "store error: {}", e),
+            StoreError::Serialization(e) => write!(f, "store serialization error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<sled::Error> for StoreError {
+    fn from(e: sled::Error) -> Self {
+        StoreError::Db(e)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self {
+        StoreError::Serialization(e)
+    }
+}
+
+// The store failing is never the client's fault
+impl ResponseError for StoreError {
+    fn error_response(&self) -> HttpResponse {
+        errors::internal(self).as_response_error().error_response()
+    }
+}
+
+impl Store {
+    pub fn open<P: AsRef<Path>>(path: P) -> StoreResult<Store> {
+        Ok(Store {
+            db: sled::open(path)?,
+            namespace: String::new(),
+        })
+    }
+
+    // Dropped when the last clone is, for tests
+    #[cfg(test)]
+    pub fn temporary() -> StoreResult<Store> {
+        Ok(Store {
+            db: sled::Config::new().temporary(true).open()?,
+            namespace: String::new(),
+        })
+    }
+
+    // The same database with the trees of a single site
+    pub fn for_site(&self, site: &str) -> Store {
+        Store {
+            db: self.db.clone(),
+            namespace: format!("{}/", site),
+        }
+    }
+
+    fn tree(&self, tree: &str) -> sled::Result<sled::Tree> {
+        self.db.open_tree(format!("{}{}", self.namespace, tree))
+    }
+
+    // Monotonic ids, unique for the lifetime of the database
+    pub fn generate_id(&self) -> StoreResult<u64> {
+        Ok(self.db.generate_id()?)
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, tree: &str, key: &str) -> StoreResult<Option<T>> {
+        match self.tree(tree)?.get(key)? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn insert<T: Serialize>(&self, tree: &str, key: &str, value: &T) -> StoreResult<()> {
+        self.tree(tree)?.insert(key, serde_json::to_vec(value)?)?;
+        Ok(())
+    }
+
+    // Atomically adds one to the counter stored at `key`(missing counters start at 0)
+    pub fn increment(&self, tree: &str, key: &str) -> StoreResult<u64> {
+        let updated = self.tree(tree)?.update_and_fetch(key, |old| {
+            let count = old
+                .and_then(|old| serde_json::from_slice::<u64>(old).ok())
+                .unwrap_or_default();
+            serde_json::to_vec(&(count + 1)).ok()
+        })?;
+
+        Ok(match updated {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => 0,
+        })
+    }
+
+    pub fn remove(&self, tree: &str, key: &str) -> StoreResult<()> {
+        self.tree(tree)?.remove(key)?;
+        Ok(())
+    }
+
+    // Returns every (key, value) pair whose key starts with `prefix`, ordered by key
+    pub fn scan_prefix<T: DeserializeOwned>(
+        &self,
+        tree: &str,
+        prefix: &str,
+    ) -> StoreResult<Vec<(String, T)>> {
+        self.tree(tree)?
+            .scan_prefix(prefix)
+            .map(decode_entry)
+            .collect()
+    }
+
+    // Returns every (key, value) pair whose key sorts at or after `start`, ordered by key
+    pub fn scan_from<T: DeserializeOwned>(
+        &self,
+        tree: &str,
+        start: &str,
+    ) -> StoreResult<Vec<(String, T)>> {
+        self.tree(tree)?.range(start..).map(decode_entry).collect()
+    }
+
+    pub fn last_with_prefix<T: DeserializeOwned>(
+        &self,
+        tree: &str,
+        prefix: &str,
+    ) -> StoreResult<Option<T>> {
+        match self.tree(tree)?.scan_prefix(prefix).next_back() {
+            Some(entry) => Ok(Some(serde_json::from_slice(&entry?.1)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn first_with_prefix<T: DeserializeOwned>(
+        &self,
+        tree: &str,
+        prefix: &str,
+    ) -> StoreResult<Option<T>> {
+        match self.tree(tree)?.scan_prefix(prefix).next() {
+            Some(entry) => Ok(Some(serde_json::from_slice(&entry?.1)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn decode_entry<T: DeserializeOwned>(
+    entry: sled::Result<(sled::IVec, sled::IVec)>,
+) -> StoreResult<(String, T)> {
+    let (key, value) = entry?;
+    Ok((
+        String::from_utf8_lossy(&key).into_owned(),
+        serde_json::from_slice(&value)?,
+    ))
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+// A directory of its own under the system's temporary one for tests, removed with everything in
+// it once dropped
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    // Files are given by their path in the directory, their parents are created too
+    pub fn with_files(files: &[(&str, &str)]) -> TempDir {
+        let path = std::env::temp_dir().join(format!(
+            "blog-test-{}-{}",
+            std::process::id(),
+            CREATED.fetch_add(1, Ordering::SeqCst)
+        ));
+        for (file, content) in files {
+            let file = path.join(file);
+            fs::create_dir_all(file.parent().unwrap_or(&path)).unwrap();
+            fs::write(file, content).unwrap();
+        }
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.path.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
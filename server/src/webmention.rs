@@ -0,0 +1,380 @@
+use crate::articles::ArticleIndex;
+use crate::clock;
+use crate::errors;
+use crate::rate_limit::{ClientAddress, RateLimiter};
+use crate::sites::Site;
+use crate::store::{Store, StoreResult};
+use actix_web::{web, HttpResponse, Result};
+use awc::Client;
+use pulldown_cmark as pc;
+use serde::Deserialize;
+use shared::constants;
+use shared::markdown::{self, Extensions};
+use shared::webmentions::{Mention, Mentions};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use url::Url;
+
+const WEBMENTIONS_TREE: &str = "webmentions";
+const MAX_FETCHED_BYTES: usize = 1024 * 1024;
+
+// Verifying a webmention fetches whatever the sender says, so senders are rate limited and only so
+// many verifications run at once
+pub struct Verifier {
+    rate_limiter: RateLimiter,
+    max_in_flight: usize,
+    in_flight: AtomicUsize,
+}
+
+impl Verifier {
+    pub fn new(rate_limiter: RateLimiter, max_in_flight: usize) -> Verifier {
+        Verifier {
+            rate_limiter,
+            max_in_flight,
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    fn start(verifier: &web::Data<Verifier>) -> Option<InFlight> {
+        verifier
+            .in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+                Some(running + 1).filter(|running| *running <= verifier.max_in_flight)
+            })
+            .ok()
+            .map(|_| InFlight(verifier.clone()))
+    }
+}
+
+// Frees the verification's slot once it's done, however it ends
+struct InFlight(web::Data<Verifier>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Deserialize)]
+pub struct WebmentionForm {
+    source: String,
+    target: String,
+}
+
+fn mention_key(article: &str, source: &str) -> String {
+    format!("{}/{}", article, source)
+}
+
+pub fn article_url(site: &Url, article: &str) -> String {
+    format!("{}#{}/{}", site, constants::ARTICLES_PATH, article)
+}
+
+// Articles live in the client's hash routes(`/#articles/<name>`) but the raw `/articles/<name>`
+// path is accepted too.
+fn target_article(index: &ArticleIndex, site: &Url, target: &Url) -> Option<String> {
+    if target.origin() != site.origin() {
+        return None;
+    }
+
+    let route = match target.fragment() {
+        Some(fragment) => fragment,
+        None => target.path().trim_start_matches('/'),
+    };
+    let article = route.strip_prefix(&format!("{}/", constants::ARTICLES_PATH))?;
+    let article = percent_encoding::percent_decode_str(article)
+        .decode_utf8()
+        .ok()?;
+
+    index.get(&article).map(|a| a.metadata.name.clone())
+}
+
+// Verification is done asynchronously as recommended by the spec, the sender only gets a 202.
+// The site's public URL is what recognizes its articles among the targets.
+pub async fn receive(
+    client: ClientAddress,
+    site: Site,
+    verifier: web::Data<Verifier>,
+    form: web::Form<WebmentionForm>,
+) -> Result<HttpResponse> {
+    if !verifier.rate_limiter.check(&client.0) {
+        return Err(errors::too_many_requests(
+            "Too many webmentions, try again later",
+        ));
+    }
+
+    let form = form.into_inner();
+    let source = Url::parse(&form.source).map_err(|_| errors::bad_request("Invalid source URL"))?;
+    let target = Url::parse(&form.target).map_err(|_| errors::bad_request("Invalid target URL"))?;
+
+    if !matches!(source.scheme(), "http" | "https") || source == target {
+        return Err(errors::bad_request("Invalid source URL"));
+    }
+
+    let article = target_article(&site.index, &site.config.site_url, &target)
+        .ok_or_else(|| errors::bad_request("Target isn't an article of this blog"))?;
+
+    let in_flight = Verifier::start(&verifier).ok_or_else(|| {
+        errors::too_many_requests("Too many webmentions being verified, try again later")
+    })?;
+    let store = site.store.clone();
+    actix_rt::spawn(async move {
+        if let Err(e) = verify(&store, &article, &source, &form.target).await {
+            println!("Couldn't verify webmention from {}: {}", source, e);
+        }
+        drop(in_flight);
+    });
+
+    Ok(HttpResponse::Accepted().finish())
+}
+
+// Anything but the public internet, e.g. loopback, private networks or the link-local addresses
+// cloud metadata services answer on
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            let shared = a == 100 && (b & 0xc0) == 64;
+            let benchmarking = a == 198 && (b & 0xfe) == 18;
+            // 240.0.0.0/4, which the broadcast address is part of
+            let reserved = a >= 240;
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || shared
+                || benchmarking
+                || reserved)
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            // NAT64(64:ff9b::/96) reaches the IPv4 address in the last 32 bits
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [.., a, b, c, d] = ip.octets();
+                return is_public(IpAddr::V4(Ipv4Addr::new(a, b, c, d)));
+            }
+            match ip.to_ipv4_mapped() {
+                Some(ip) => is_public(IpAddr::V4(ip)),
+                None => {
+                    let unique_local = (segments[0] & 0xfe00) == 0xfc00;
+                    let link_local = (segments[0] & 0xffc0) == 0xfe80;
+                    !(ip.is_loopback()
+                        || ip.is_unspecified()
+                        || ip.is_multicast()
+                        || unique_local
+                        || link_local)
+                }
+            }
+        }
+    }
+}
+
+// Senders could otherwise make us request our own network. The source is only resolved here and
+// the request connects to the address checked, so its name can't point somewhere else by then.
+async fn public_address(source: &Url) -> Result<SocketAddr, String> {
+    let url = source.clone();
+    let addresses = web::block(move || url.socket_addrs(|| None))
+        .await
+        .map_err(|e| e.to_string())?;
+    match addresses.first() {
+        Some(address) if addresses.iter().all(|address| is_public(address.ip())) => Ok(*address),
+        _ => Err(String::from("the source isn't a public address")),
+    }
+}
+
+async fn verify(store: &Store, article: &str, source: &Url, target: &str) -> Result<(), String> {
+    let address = public_address(source).await?;
+    // Redirects would go to addresses nobody checked, a source that redirects isn't verified
+    let mut response = Client::builder()
+        .disable_redirects()
+        .finish()
+        .get(source.as_str())
+        .address(address)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let body = response
+        .body()
+        .limit(MAX_FETCHED_BYTES)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let key = mention_key(article, source.as_str());
+    let result: StoreResult<()> =
+        if response.status().is_success() && String::from_utf8_lossy(&body).contains(target) {
+            store.insert(
+                WEBMENTIONS_TREE,
+                &key,
+                &Mention {
+                    source: source.to_string(),
+                    target: target.to_string(),
+                    verified_at: clock::now(),
+                },
+            )
+        } else {
+            // The source no longer links to us(or is gone) so the mention is dropped
+            store.remove(WEBMENTIONS_TREE, &key)
+        };
+
+    result.map_err(|e| e.to_string())
+}
+
+pub async fn list(site: Site, article: web::Path<String>) -> Result<HttpResponse> {
+    let article = match site.index.resolve(&article) {
+        Some(article) => article.metadata.name.clone(),
+        None => return Err(errors::not_found("Unknown article")),
+    };
+    let mentions = site
+        .store
+        .scan_prefix(WEBMENTIONS_TREE, &format!("{}/", article))?
+        .into_iter()
+        .map(|(_, mention)| mention)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(Mentions { article, mentions }))
+}
+
+fn external_links(site: &Url, content: &str, extensions: Extensions) -> Vec<Url> {
+    markdown::parser(content, extensions)
+        .filter_map(|event| match event {
+            pc::Event::Start(pc::Tag::Link(_, url, _)) => Url::parse(&url).ok(),
+            _ => None,
+        })
+        .filter(|url| matches!(url.scheme(), "http" | "https") && url.origin() != site.origin())
+        .collect()
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    ["\"", "'"].iter().find_map(|quote| {
+        let start = tag.find(&format!("{}={}", name, quote))? + name.len() + 2;
+        let end = tag[start..].find(quote)? + start;
+        Some(&tag[start..end])
+    })
+}
+
+fn has_webmention_rel(rel: &str) -> bool {
+    rel.split_whitespace().any(|rel| rel == "webmention")
+}
+
+// `Link: <https://example.com/webmention>; rel="webmention"`
+fn link_header_endpoint(header: &str) -> Option<&str> {
+    header.split(',').find_map(|link| {
+        let mut parts = link.split(';');
+        let url = parts.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+        parts
+            .filter_map(|param| param.trim().strip_prefix("rel="))
+            .any(|rel| has_webmention_rel(rel.trim_matches('"')))
+            .then_some(url)
+    })
+}
+
+// First `<link>` or `<a>` with `rel="webmention"`, good enough for the pages we link to
+fn html_endpoint(html: &str) -> Option<&str> {
+    html.split('<')
+        .filter(|tag| tag.starts_with("link ") || tag.starts_with("a "))
+        .map(|tag| tag.split('>').next().unwrap_or(tag))
+        .find(|tag| attribute(tag, "rel").is_some_and(has_webmention_rel))
+        .and_then(|tag| attribute(tag, "href"))
+}
+
+async fn discover_endpoint(client: &Client, target: &Url) -> Option<Url> {
+    let mut response = client.get(target.as_str()).send().await.ok()?;
+    let from_header = response
+        .headers()
+        .get_all("Link")
+        .filter_map(|value| value.to_str().ok())
+        .find_map(link_header_endpoint)
+        .and_then(|endpoint| target.join(endpoint).ok());
+    if from_header.is_some() {
+        return from_header;
+    }
+
+    let body = response.body().limit(MAX_FETCHED_BYTES).await.ok()?;
+    html_endpoint(&String::from_utf8_lossy(&body)).and_then(|endpoint| target.join(endpoint).ok())
+}
+
+async fn send(client: &Client, source: &str, target: &Url) -> Result<(), String> {
+    let endpoint = match discover_endpoint(client, target).await {
+        Some(endpoint) => endpoint,
+        // Most sites don't support webmentions, nothing to do
+        None => return Ok(()),
+    };
+
+    client
+        .post(endpoint.as_str())
+        .send_form(&[("source", source), ("target", target.as_str())])
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// Notifies every site linked from the given articles, meant to run when they are published
+pub async fn send_webmentions(site: Site, articles: Vec<String>) {
+    let client = Client::default();
+    let site_url = &site.config.site_url;
+    for article in articles.iter().filter_map(|name| site.index.get(name)) {
+        let source = article_url(site_url, &article.metadata.name);
+        for target in external_links(site_url, &article.content, site.config.extensions) {
+            if let Err(e) = send(&client, &source, &target).await {
+                println!("Couldn't send webmention to {}: {}", target, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_public_addresses_are_fetched() {
+        for (ip, public) in &[
+            ("93.184.216.34", true),
+            ("2606:2800:220:1:248:1893:25c8:1946", true),
+            ("127.0.0.1", false),
+            ("10.1.2.3", false),
+            ("172.16.0.1", false),
+            ("192.168.1.1", false),
+            ("169.254.169.254", false),
+            ("100.64.0.1", false),
+            ("0.0.0.0", false),
+            ("::1", false),
+            ("::", false),
+            ("fd00::1", false),
+            ("fe80::1", false),
+            ("::ffff:127.0.0.1", false),
+            ("224.0.0.1", false),
+            ("239.255.255.250", false),
+            ("240.0.0.1", false),
+            ("255.255.255.255", false),
+            ("198.18.0.1", false),
+            ("198.19.255.255", false),
+            ("198.20.0.1", true),
+            ("ff02::1", false),
+            ("ff0e::1", false),
+            ("64:ff9b::7f00:1", false),
+            ("64:ff9b::a00:1", false),
+            ("64:ff9b::5db8:d822", true),
+        ] {
+            assert_eq!(is_public(ip.parse().unwrap()), *public, "{}", ip);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn sources_resolving_to_our_network_are_refused() {
+        for source in &[
+            "http://127.0.0.1:8080/",
+            "http://[::1]/",
+            "http://localhost/",
+        ] {
+            let source = Url::parse(source).unwrap();
+            assert!(public_address(&source).await.is_err(), "{}", source);
+        }
+        let source = Url::parse("http://93.184.216.34:8080/").unwrap();
+        assert_eq!(
+            public_address(&source).await,
+            Ok("93.184.216.34:8080".parse().unwrap())
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use crate::article_list::ArticleLink;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ArchiveMonth {
+    // 1 to 12
+    pub month: u32,
+    pub articles: Vec<ArticleLink>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ArchiveYear {
+    pub year: u32,
+    // Articles published that year, adding up every month
+    pub count: usize,
+    pub months: Vec<ArchiveMonth>,
+}
+
+// Every dated article grouped by publication year and month, newest first
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Archive {
+    pub years: Vec<ArchiveYear>,
+}
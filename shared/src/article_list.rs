@@ -1,6 +1,89 @@
+use crate::authors::AuthorLink;
+use crate::markdown::Extensions;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// How the article list is ordered, `oldest` being the publication order
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Newest,
+    #[default]
+    Oldest,
+    Title,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ArticleLink {
+    pub name: String,
+    pub title: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ArticleMetadata {
+    // File name of the article, which is also what identifies it in the routes
+    pub name: String,
+    pub title: String,
+    pub word_count: usize,
+    // In minutes
+    pub reading_time: u32,
+    // Publication date, seconds since the unix epoch
+    pub date: Option<u64>,
+    pub tags: Vec<String>,
+    pub authors: Vec<AuthorLink>,
+    // Articles linking to this one
+    pub backlinks: Vec<ArticleLink>,
+    // Articles sharing tags or terms with this one, most related first
+    pub related: Vec<ArticleLink>,
+    // Neighbours in publication order
+    pub previous: Option<ArticleLink>,
+    pub next: Option<ArticleLink>,
+}
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Articles {
-    pub articles: Vec<String>,
+    pub articles: Vec<ArticleMetadata>,
+    // Articles per page, as configured in the server
+    pub page_size: usize,
+    // What the previews are rendered with
+    pub extensions: Extensions,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 3] = [SortOrder::Newest, SortOrder::Oldest, SortOrder::Title];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Newest => "newest",
+            SortOrder::Oldest => "oldest",
+            SortOrder::Title => "title",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortOrder::Newest => "Newest first",
+            SortOrder::Oldest => "Oldest first",
+            SortOrder::Title => "By title",
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SortOrder::ALL
+            .iter()
+            .copied()
+            .find(|order| order.as_str() == s)
+            .ok_or_else(|| format!("Unknown sort order `{}`", s))
+    }
 }
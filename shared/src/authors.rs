@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+// Where to find or contact an author, e.g. a mail address or a social network profile
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ProfileLink {
+    pub label: String,
+    pub url: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Author {
+    // What articles list in their `authors`
+    pub id: String,
+    pub name: String,
+    pub avatar: Option<String>,
+    pub location: Option<String>,
+    // Markdown
+    pub bio: String,
+    pub links: Vec<ProfileLink>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Authors {
+    pub authors: Vec<Author>,
+}
+
+// An author as shown along their articles
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct AuthorLink {
+    pub id: String,
+    pub name: String,
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub id: u64,
+    pub article: String,
+    pub parent: Option<u64>,
+    pub author: String,
+    pub body: String,
+    // Seconds since the unix epoch
+    pub timestamp: u64,
+    pub status: CommentStatus,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct NewComment {
+    pub parent: Option<u64>,
+    pub author: String,
+    pub body: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CommentThread {
+    pub comment: Comment,
+    pub replies: Vec<CommentThread>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Comments {
+    pub article: String,
+    pub threads: Vec<CommentThread>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationAction {
+    Approve,
+    Reject,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ModerationDecision {
+    pub action: ModerationAction,
+}
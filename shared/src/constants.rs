@@ -1,3 +1,21 @@
 pub const ARTICLE_LIST_URI: &str = "/article_list";
 pub const ARTICLES_PATH: &str = "articles";
 pub const STATIC_URL: &str = "./dist";
+pub const REVISIONS_URI: &str = "/api/revisions";
+pub const COMMENTS_URI: &str = "/api/comments";
+pub const MODERATION_URI: &str = "/api/moderation";
+pub const MAX_COMMENT_AUTHOR_LENGTH: usize = 64;
+pub const MAX_COMMENT_LENGTH: usize = 5000;
+pub const WEBMENTION_URI: &str = "/webmention";
+pub const WEBMENTIONS_URI: &str = "/api/webmentions";
+pub const STATS_URI: &str = "/api/stats";
+// Lets the client forward `document.referrer`, requests made by the client itself always have the
+// blog as referrer.
+pub const PAGE_REFERRER_HEADER: &str = "X-Page-Referrer";
+pub const ARTICLE_METADATA_URI: &str = "/api/articles";
+pub const PLAYGROUND_URI: &str = "/api/playground";
+pub const ARCHIVE_URI: &str = "/archive";
+pub const SITE_URI: &str = "/api/site";
+pub const THEME_URI: &str = "/api/site/theme.css";
+pub const ABOUT_URI: &str = "/api/site/about";
+pub const AUTHORS_URI: &str = "/api/authors";
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+// Body of every error response of the API
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ApiError {
+    // The response's HTTP status, so the body makes sense on its own
+    pub status: u16,
+    pub message: String,
+    // Only for internal errors, it's logged along the actual error so a report can be matched
+    // with it
+    pub id: Option<String>,
+}
@@ -1,3 +1,13 @@
+pub mod archive;
 pub mod article_list;
+pub mod authors;
+pub mod comments;
 pub mod constants;
+pub mod errors;
+pub mod markdown;
 pub mod pagination_indexes;
+pub mod playground;
+pub mod revisions;
+pub mod site;
+pub mod stats;
+pub mod webmentions;
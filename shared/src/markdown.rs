@@ -0,0 +1,752 @@
+use crate::constants;
+use crate::playground;
+use pulldown_cmark as pc;
+use pulldown_cmark::escape::{escape_href, escape_html};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+// A paragraph containing only this is replaced by the table of contents
+pub const TOC_MARKER: &str = "[TOC]";
+
+// Markdown extensions on top of CommonMark. Whatever parses articles(the client rendering them or
+// the server computing their metadata) should use the same ones, those the server has configured
+// for the site.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Extensions {
+    pub tables: bool,
+    pub footnotes: bool,
+    pub strikethrough: bool,
+    pub task_lists: bool,
+    pub smart_punctuation: bool,
+    // `## Heading {#custom-id .class}`
+    pub heading_attributes: bool,
+}
+
+pub const ALL_EXTENSIONS: Extensions = Extensions {
+    tables: true,
+    footnotes: true,
+    strikethrough: true,
+    task_lists: true,
+    smart_punctuation: true,
+    heading_attributes: true,
+};
+
+impl Default for Extensions {
+    fn default() -> Self {
+        ALL_EXTENSIONS
+    }
+}
+
+impl Extensions {
+    // Heading attributes aren't supported by pulldown-cmark, `render` takes care of them
+    pub fn parser_options(&self) -> pc::Options {
+        let mut options = pc::Options::empty();
+        options.set(pc::Options::ENABLE_TABLES, self.tables);
+        options.set(pc::Options::ENABLE_FOOTNOTES, self.footnotes);
+        options.set(pc::Options::ENABLE_STRIKETHROUGH, self.strikethrough);
+        options.set(pc::Options::ENABLE_TASKLISTS, self.task_lists);
+        options.set(
+            pc::Options::ENABLE_SMART_PUNCTUATION,
+            self.smart_punctuation,
+        );
+        options
+    }
+}
+
+pub fn parser(markdown: &str, extensions: Extensions) -> pc::Parser<'_> {
+    pc::Parser::new_ext(markdown, extensions.parser_options())
+}
+
+// A code block as it is in the markdown source, for what works on the source instead of on the
+// rendered HTML(shortcodes, diagrams, the snippets tests...)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    // `None` for indented blocks
+    pub info: Option<String>,
+    // From the opening fence to the end of the closing one, or of the article if it's never closed
+    pub range: Range<usize>,
+    // Where the first line of code starts
+    pub code_start: usize,
+    // Without the indentation or `>` of the list item or quote the block is in
+    pub code: String,
+}
+
+pub fn code_blocks(markdown: &str, extensions: Extensions) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<CodeBlock> = None;
+    for (event, range) in parser(markdown, extensions).into_offset_iter() {
+        match event {
+            pc::Event::Start(pc::Tag::CodeBlock(kind)) => {
+                let info = match kind {
+                    pc::CodeBlockKind::Fenced(info) => Some(info.to_string()),
+                    pc::CodeBlockKind::Indented => None,
+                };
+                open = Some(CodeBlock {
+                    info,
+                    code_start: range.end,
+                    range,
+                    code: String::new(),
+                });
+            }
+            pc::Event::Text(text) => {
+                if let Some(block) = &mut open {
+                    if block.code.is_empty() {
+                        block.code_start = range.start;
+                    }
+                    block.code.push_str(&text);
+                }
+            }
+            pc::Event::End(pc::Tag::CodeBlock(_)) => blocks.extend(open.take()),
+            _ => {}
+        }
+    }
+    blocks
+}
+
+// Where the code blocks and code spans are in the markdown source, what's in them is shown as is
+pub fn code_ranges(markdown: &str, extensions: Extensions) -> Vec<Range<usize>> {
+    parser(markdown, extensions)
+        .into_offset_iter()
+        .filter(|(event, _)| {
+            matches!(
+                event,
+                pc::Event::Start(pc::Tag::CodeBlock(_)) | pc::Event::Code(_)
+            )
+        })
+        .map(|(_, range)| range)
+        .collect()
+}
+
+pub struct RenderOptions<'a> {
+    // Prefix for the links pointing to a heading, e.g. `#articles/<name>/`.
+    // Without one headings get no anchor links and in-page links are left untouched.
+    pub anchor_prefix: Option<&'a str>,
+    pub extensions: Extensions,
+    // Whether runnable Rust code blocks are marked to get a run button
+    pub playground: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocEntry {
+    pub level: u32,
+    pub id: String,
+    pub title: String,
+}
+
+pub struct Rendered {
+    pub html: String,
+    pub toc: Vec<TocEntry>,
+    // Whether the table of contents was already placed inline through `TOC_MARKER`
+    pub inline_toc: bool,
+}
+
+struct Heading {
+    level: u32,
+    id: String,
+    title: String,
+    classes: Vec<String>,
+}
+
+#[derive(Default)]
+struct HeadingAttributes {
+    id: Option<String>,
+    classes: Vec<String>,
+}
+
+// A footnote definition, pulled out of the text to be rendered as an endnote
+struct Footnote<'a> {
+    label: String,
+    // Of the endnote and of its first reference, numbered so labels can't clash with headings
+    id: String,
+    reference_id: String,
+    events: Vec<pc::Event<'a>>,
+}
+
+fn escaped_html(text: &str) -> String {
+    let mut escaped = String::new();
+    // Writing to a String can't fail
+    let _ = escape_html(&mut escaped, text);
+    escaped
+}
+
+fn escaped_href(href: &str) -> String {
+    let mut escaped = String::new();
+    let _ = escape_href(&mut escaped, href);
+    escaped
+}
+
+// Lowercase alphanumerics separated by single dashes: "Why? No, seriously" -> "why-no-seriously"
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.trim_end_matches('-').to_string()
+}
+
+// Repeated titles get a numeric suffix so ids stay unique and stable between renders
+fn unique_id(used: &mut HashSet<String>, title: &str) -> String {
+    let base = match slugify(title) {
+        slug if slug.is_empty() => "section".to_string(),
+        slug => slug,
+    };
+
+    let mut id = base.clone();
+    let mut suffix = 1;
+    while !used.insert(id.clone()) {
+        id = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    id
+}
+
+// Parses a trailing `{#id .class}` block, returning the text without it
+fn split_heading_attributes(text: &str) -> Option<(&str, HeadingAttributes)> {
+    let trimmed = text.trim_end();
+    let start = trimmed.rfind('{')?;
+    let block = trimmed[start..].strip_prefix('{')?.strip_suffix('}')?;
+    // `struct Foo {}` is just a heading about code
+    if block.trim().is_empty() {
+        return None;
+    }
+
+    let mut attributes = HeadingAttributes::default();
+    for attribute in block.split_whitespace() {
+        let name = |prefix: char| attribute.strip_prefix(prefix).filter(|n| !n.is_empty());
+        if let Some(id) = name('#') {
+            attributes.id = Some(id.to_string());
+        } else if let Some(class) = name('.') {
+            attributes.classes.push(class.to_string());
+        } else {
+            return None;
+        }
+    }
+
+    Some((trimmed[..start].trim_end(), attributes))
+}
+
+// Strips the attributes block from the last text of every heading, returning the attributes in
+// heading order
+fn take_heading_attributes(events: &mut [pc::Event]) -> Vec<HeadingAttributes> {
+    let mut attributes = Vec::new();
+    let mut last_text = None;
+    for i in 0..events.len() {
+        match &events[i] {
+            pc::Event::Start(pc::Tag::Heading(_)) => last_text = None,
+            pc::Event::Text(_) => last_text = Some(i),
+            pc::Event::End(pc::Tag::Heading(_)) => {
+                let split = last_text.and_then(|i| match &events[i] {
+                    pc::Event::Text(text) => split_heading_attributes(text)
+                        .map(|(text, attributes)| (i, text.to_string(), attributes)),
+                    _ => None,
+                });
+                attributes.push(match split {
+                    Some((i, text, heading_attributes)) => {
+                        events[i] = pc::Event::Text(text.into());
+                        heading_attributes
+                    }
+                    None => HeadingAttributes::default(),
+                });
+            }
+            _ => {}
+        }
+    }
+    attributes
+}
+
+fn collect_headings(events: &[pc::Event], attributes: Vec<HeadingAttributes>) -> Vec<Heading> {
+    let mut used = HashSet::new();
+    let mut headings = Vec::new();
+    let mut attributes = attributes.into_iter();
+    let mut current: Option<(u32, String)> = None;
+    for event in events {
+        match event {
+            pc::Event::Start(pc::Tag::Heading(level)) => current = Some((*level, String::new())),
+            pc::Event::Text(text) | pc::Event::Code(text) => {
+                if let Some((_, title)) = current.as_mut() {
+                    title.push_str(text);
+                }
+            }
+            pc::Event::End(pc::Tag::Heading(_)) => {
+                if let Some((level, title)) = current.take() {
+                    let attributes = attributes.next().unwrap_or_default();
+                    let id = match attributes.id {
+                        Some(id) if used.insert(id.clone()) => id,
+                        _ => unique_id(&mut used, &title),
+                    };
+                    headings.push(Heading {
+                        level,
+                        id,
+                        title: title.trim_end().to_string(),
+                        classes: attributes.classes,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+// Removes the footnote definitions from the events, keyed by label
+fn take_footnotes<'a>(
+    events: Vec<pc::Event<'a>>,
+) -> (Vec<pc::Event<'a>>, HashMap<String, Vec<pc::Event<'a>>>) {
+    let mut remaining = Vec::with_capacity(events.len());
+    let mut footnotes = HashMap::new();
+    let mut current: Option<(String, Vec<pc::Event>)> = None;
+    for event in events {
+        match event {
+            pc::Event::Start(pc::Tag::FootnoteDefinition(label)) => {
+                current = Some((label.to_string(), Vec::new()))
+            }
+            pc::Event::End(pc::Tag::FootnoteDefinition(_)) => {
+                if let Some((label, events)) = current.take() {
+                    footnotes.insert(label, events);
+                }
+            }
+            event => match current.as_mut() {
+                Some((_, events)) => events.push(event),
+                None => remaining.push(event),
+            },
+        }
+    }
+    (remaining, footnotes)
+}
+
+fn footnote_link(anchor_prefix: Option<&str>, id: &str) -> Option<String> {
+    anchor_prefix.map(|prefix| escaped_href(&format!("{}{}", prefix, id)))
+}
+
+// Endnotes in order of first reference, each one linking back to where it was referenced
+fn push_endnotes<'a>(
+    output: &mut Vec<pc::Event<'a>>,
+    endnotes: Vec<Footnote<'a>>,
+    anchor_prefix: Option<&str>,
+) {
+    if endnotes.is_empty() {
+        return;
+    }
+
+    output.push(pc::Event::Html("<section class=\"footnotes\"><ol>".into()));
+    for mut endnote in endnotes {
+        let backref = match footnote_link(anchor_prefix, &endnote.reference_id) {
+            Some(href) => format!("<a class=\"footnote-backref\" href=\"{}\">↩</a>", href),
+            None => String::new(),
+        };
+        output.push(pc::Event::Html(
+            format!("<li id=\"{}\">", escaped_html(&endnote.id)).into(),
+        ));
+        // Inside the last paragraph if there's one, so it isn't left alone in its own line
+        let closing = match endnote.events.last() {
+            Some(pc::Event::End(pc::Tag::Paragraph)) => endnote.events.pop(),
+            _ => None,
+        };
+        output.append(&mut endnote.events);
+        output.push(pc::Event::Html(backref.into()));
+        output.extend(closing);
+        output.push(pc::Event::Html("</li>".into()));
+    }
+    output.push(pc::Event::Html("</ol></section>".into()));
+}
+
+// Nested lists following the heading levels
+pub fn toc_html(toc: &[TocEntry], anchor_prefix: &str) -> String {
+    let mut html = String::from("<nav class=\"toc\">");
+    let mut levels: Vec<u32> = Vec::new();
+    for entry in toc {
+        while matches!(levels.last(), Some(&level) if level > entry.level) {
+            html.push_str("</li></ul>");
+            levels.pop();
+        }
+
+        match levels.last() {
+            Some(&level) if level == entry.level => html.push_str("</li>"),
+            _ => {
+                html.push_str("<ul>");
+                levels.push(entry.level);
+            }
+        }
+
+        html.push_str(&format!(
+            "<li><a href=\"{}\">{}</a>",
+            escaped_href(&format!("{}{}", anchor_prefix, entry.id)),
+            escaped_html(&entry.title)
+        ));
+    }
+
+    for _ in levels {
+        html.push_str("</li></ul>");
+    }
+    html.push_str("</nav>");
+    html
+}
+
+// Number of events up to the end of a paragraph starting at `events[0]` if it only contains the
+// TOC marker. The marker can be split in several text events because of the brackets.
+fn toc_marker_len(events: &[pc::Event]) -> Option<usize> {
+    let mut text = String::new();
+    for (i, event) in events.iter().enumerate().skip(1) {
+        match event {
+            pc::Event::Text(t) => text.push_str(t),
+            pc::Event::End(pc::Tag::Paragraph) if text.trim() == TOC_MARKER => return Some(i + 1),
+            _ => return None,
+        }
+    }
+    None
+}
+
+// Number of events up to the end of the code block starting at `events[0]`, and its text
+fn code_block(events: &[pc::Event]) -> (usize, String) {
+    let mut code = String::new();
+    for (i, event) in events.iter().enumerate().skip(1) {
+        match event {
+            pc::Event::Text(text) => code.push_str(text),
+            pc::Event::End(pc::Tag::CodeBlock(_)) => return (i + 1, code),
+            _ => {}
+        }
+    }
+    (events.len(), code)
+}
+
+// What follows the opening fence: ```rust {3,5-7} title="main.rs" linenos
+#[derive(Default)]
+struct CodeInfo<'a> {
+    // Including rustdoc-like flags, e.g. `rust,ignore`
+    lang: &'a str,
+    // Inclusive ranges of 1-based line numbers
+    highlighted: Vec<(usize, usize)>,
+    title: Option<&'a str>,
+    line_numbers: bool,
+}
+
+fn parse_line_ranges(ranges: &str) -> Vec<(usize, usize)> {
+    ranges
+        .split(',')
+        .filter_map(|range| {
+            let mut bounds = range.splitn(2, '-').map(|n| n.trim().parse::<usize>());
+            let start = bounds.next()?.ok()?;
+            let end = match bounds.next() {
+                Some(end) => end.ok()?,
+                None => start,
+            };
+            Some((start, end))
+        })
+        .collect()
+}
+
+impl<'a> CodeInfo<'a> {
+    fn parse(info: &'a str) -> CodeInfo<'a> {
+        let mut code_info = CodeInfo::default();
+        let mut rest = info.trim_start();
+        while !rest.is_empty() {
+            let (token, after) = if let Some(ranges) = rest.strip_prefix('{') {
+                let end = ranges.find('}').unwrap_or(ranges.len());
+                code_info.highlighted = parse_line_ranges(&ranges[..end]);
+                ("", ranges.get(end + 1..).unwrap_or(""))
+            } else if let Some(quoted) = rest.strip_prefix("title=\"") {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                code_info.title = Some(&quoted[..end]);
+                ("", quoted.get(end + 1..).unwrap_or(""))
+            } else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            };
+
+            match token {
+                "" => {}
+                "linenos" => code_info.line_numbers = true,
+                token if token.starts_with("title=") => code_info.title = Some(&token[6..]),
+                token if code_info.lang.is_empty() => code_info.lang = token,
+                _ => {}
+            }
+            rest = after.trim_start();
+        }
+        code_info
+    }
+
+    fn is_highlighted(&self, line: usize) -> bool {
+        self.highlighted
+            .iter()
+            .any(|(start, end)| (*start..=*end).contains(&line))
+    }
+}
+
+// Code blocks get a wrapper for the client to add a copy button, with the title, line numbers and
+// highlighted lines drawn next to and behind the code so highlight.js can still color it.
+// Rust code is shown without its hidden lines, and runnable blocks are wrapped with the full code
+// for the client to add the run button.
+fn code_block_html(info: &str, code: &str, playground: bool) -> String {
+    let info = CodeInfo::parse(info);
+    let (shown, full) = if playground::is_rust(info.lang) {
+        playground::split_hidden_lines(code)
+    } else {
+        (code.to_string(), code.to_string())
+    };
+    let lines = shown.lines().count();
+
+    let mut html = String::from("<div class=\"code-block\">");
+    if let Some(title) = info.title {
+        html += &format!("<div class=\"code-title\">{}</div>", escaped_html(title));
+    }
+    html += "<div class=\"code-body\">";
+    if info.line_numbers {
+        let numbers: Vec<String> = (1..=lines).map(|line| line.to_string()).collect();
+        html += &format!(
+            "<pre class=\"code-line-numbers\" aria-hidden=\"true\">{}</pre>",
+            numbers.join("\n")
+        );
+    }
+    html += "<div class=\"code-content\">";
+    for line in (1..=lines).filter(|line| info.is_highlighted(*line)) {
+        html += &format!(
+            "<div class=\"code-highlight\" style=\"--line: {}\" aria-hidden=\"true\"></div>",
+            line - 1
+        );
+    }
+
+    let lang = info.lang.split(',').next().unwrap_or("");
+    let class = if lang.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"language-{}\"", escaped_html(lang))
+    };
+    html += &format!(
+        "<pre><code{}>{}</code></pre></div></div></div>",
+        class,
+        escaped_html(&shown)
+    );
+
+    if playground && playground::is_runnable(info.lang) {
+        format!(
+            "<div class=\"playground\" data-code=\"{}\">{}</div>",
+            escaped_html(&full),
+            html
+        )
+    } else {
+        html
+    }
+}
+
+// Links to a heading of the same article, as opposed to `#articles/...` links to other articles
+fn is_in_page_link(dest: &str) -> bool {
+    dest.starts_with('#') && !dest.starts_with(&format!("#{}/", constants::ARTICLES_PATH))
+}
+
+// Takes the heading attributes out of the events when enabled
+fn headings(events: &mut [pc::Event], extensions: Extensions) -> Vec<Heading> {
+    let attributes = if extensions.heading_attributes {
+        take_heading_attributes(events)
+    } else {
+        Vec::new()
+    };
+    collect_headings(events, attributes)
+}
+
+// Ids of every heading, the same `render` gives them
+pub fn heading_ids(markdown: &str, extensions: Extensions) -> Vec<String> {
+    let events: Vec<pc::Event> = parser(markdown, extensions).collect();
+    let (mut events, _) = take_footnotes(events);
+    headings(&mut events, extensions)
+        .into_iter()
+        .map(|heading| heading.id)
+        .collect()
+}
+
+pub fn render(markdown: &str, options: &RenderOptions) -> Rendered {
+    let events: Vec<pc::Event> = parser(markdown, options.extensions).collect();
+    let (mut events, mut footnotes) = take_footnotes(events);
+    let headings = headings(&mut events, options.extensions);
+    // The article title isn't worth listing
+    let toc: Vec<TocEntry> = headings
+        .iter()
+        .filter(|heading| heading.level > 1)
+        .map(|heading| TocEntry {
+            level: heading.level,
+            id: heading.id.clone(),
+            title: heading.title.clone(),
+        })
+        .collect();
+
+    let mut output = Vec::with_capacity(events.len());
+    let mut used_ids: HashSet<String> = headings.iter().map(|h| h.id.clone()).collect();
+    let mut headings = headings.iter();
+    let mut current_heading: Option<&Heading> = None;
+    let mut endnotes: Vec<Footnote> = Vec::new();
+    let mut inline_toc = false;
+    let mut i = 0;
+    while i < events.len() {
+        match &events[i] {
+            pc::Event::Start(pc::Tag::Paragraph) => {
+                if let (Some(prefix), Some(len)) =
+                    (options.anchor_prefix, toc_marker_len(&events[i..]))
+                {
+                    output.push(pc::Event::Html(toc_html(&toc, prefix).into()));
+                    inline_toc = true;
+                    i += len;
+                    continue;
+                }
+                output.push(events[i].clone());
+            }
+            pc::Event::Start(pc::Tag::Heading(level)) => {
+                current_heading = headings.next();
+                let (id, classes) = current_heading.map_or((String::new(), String::new()), |h| {
+                    (escaped_html(&h.id), escaped_html(&h.classes.join(" ")))
+                });
+                let class = if classes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" class=\"{}\"", classes)
+                };
+                output.push(pc::Event::Html(
+                    format!("<h{} id=\"{}\"{}>", level, id, class).into(),
+                ));
+            }
+            pc::Event::End(pc::Tag::Heading(level)) => {
+                let anchor = match (options.anchor_prefix, current_heading) {
+                    (Some(prefix), Some(heading)) => format!(
+                        "<a class=\"heading-anchor\" href=\"{}\">#</a>",
+                        escaped_href(&format!("{}{}", prefix, heading.id))
+                    ),
+                    _ => String::new(),
+                };
+                output.push(pc::Event::Html(format!("{}</h{}>", anchor, level).into()));
+            }
+            pc::Event::Start(pc::Tag::Link(link_type, dest, title)) if is_in_page_link(dest) => {
+                match options.anchor_prefix {
+                    Some(prefix) => output.push(pc::Event::Start(pc::Tag::Link(
+                        *link_type,
+                        format!("{}{}", prefix, &dest[1..]).into(),
+                        title.clone(),
+                    ))),
+                    None => output.push(events[i].clone()),
+                }
+            }
+            pc::Event::Start(pc::Tag::CodeBlock(kind)) => {
+                let info = match kind {
+                    pc::CodeBlockKind::Fenced(info) => info,
+                    pc::CodeBlockKind::Indented => "",
+                };
+                let (len, code) = code_block(&events[i..]);
+                output.push(pc::Event::Html(
+                    code_block_html(info, &code, options.playground).into(),
+                ));
+                i += len;
+                continue;
+            }
+            pc::Event::FootnoteReference(label) => {
+                let label = label.to_string();
+                let (number, first_reference) = match endnotes
+                    .iter()
+                    .position(|note| note.label == label)
+                {
+                    Some(position) => (position + 1, false),
+                    None => {
+                        // References without definition are left as they were written
+                        let events = match footnotes.remove(&label) {
+                            Some(events) => events,
+                            None => {
+                                output.push(pc::Event::Text(format!("[^{}]", label).into()));
+                                i += 1;
+                                continue;
+                            }
+                        };
+                        let number = endnotes.len() + 1;
+                        endnotes.push(Footnote {
+                            label,
+                            id: unique_id(&mut used_ids, &format!("fn-{}", number)),
+                            reference_id: unique_id(&mut used_ids, &format!("fnref-{}", number)),
+                            events,
+                        });
+                        (endnotes.len(), true)
+                    }
+                };
+
+                // Only the first reference gets an id, it's where the endnote links back to
+                let endnote = &endnotes[number - 1];
+                let reference_id = if first_reference {
+                    format!(" id=\"{}\"", escaped_html(&endnote.reference_id))
+                } else {
+                    String::new()
+                };
+                let reference = match footnote_link(options.anchor_prefix, &endnote.id) {
+                    Some(href) => format!(
+                        "<sup class=\"footnote-reference\"{}><a href=\"{}\">{}</a></sup>",
+                        reference_id, href, number
+                    ),
+                    None => format!("<sup class=\"footnote-reference\">{}</sup>", number),
+                };
+                output.push(pc::Event::Html(reference.into()));
+            }
+            event => output.push(event.clone()),
+        }
+        i += 1;
+    }
+
+    push_endnotes(&mut output, endnotes, options.anchor_prefix);
+
+    let mut html = String::new();
+    pc::html::push_html(&mut html, output.into_iter());
+    Rendered {
+        html,
+        toc,
+        inline_toc,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_html(markdown: &str) -> String {
+        let options = RenderOptions {
+            anchor_prefix: Some("#articles/a/"),
+            extensions: ALL_EXTENSIONS,
+            playground: false,
+        };
+        render(markdown, &options).html
+    }
+
+    #[test]
+    fn footnotes_get_their_own_ids() {
+        let html =
+            render_html("# Fn 1\n\nA[^a b] B[^a-b] C[^a b]\n\n[^a b]: First\n\n[^a-b]: Second\n");
+        for expected in &[
+            "<h1 id=\"fn-1\">",
+            "<sup class=\"footnote-reference\" id=\"fnref-1\"><a href=\"#articles/a/fn-1-1\">1</a>",
+            "<sup class=\"footnote-reference\" id=\"fnref-2\"><a href=\"#articles/a/fn-2\">2</a>",
+            "<sup class=\"footnote-reference\"><a href=\"#articles/a/fn-1-1\">1</a>",
+            "<li id=\"fn-1-1\">",
+            "<li id=\"fn-2\">",
+        ] {
+            assert!(html.contains(expected), "{} in {}", expected, html);
+        }
+    }
+
+    #[test]
+    fn only_ids_and_classes_are_heading_attributes() {
+        for (markdown, id, title) in &[
+            ("## Title {#custom .wide}", "custom", "Title"),
+            ("## struct Foo {}", "struct-foo", "struct Foo {}"),
+            ("## Set {a, b}", "set-a-b", "Set {a, b}"),
+            ("## Empty {#}", "empty", "Empty {#}"),
+        ] {
+            let rendered = render(
+                markdown,
+                &RenderOptions {
+                    anchor_prefix: None,
+                    extensions: ALL_EXTENSIONS,
+                    playground: false,
+                },
+            );
+            assert_eq!(rendered.toc[0].id, *id, "{}", markdown);
+            assert_eq!(rendered.toc[0].title, *title, "{}", markdown);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// Pages are numbered from 1, the way they're shown and routed
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageLink {
+    Page(usize),
+    // Pages left out between two links
+    Ellipsis,
+}
+
+// An empty list still has its first page
+pub fn page_count(item_count: usize, page_size: usize) -> usize {
+    item_count.div_ceil(page_size).max(1)
+}
+
+// Range of the items shown in the page, `None` if there's no such page
+pub fn page_indexes(
+    page_number: usize,
+    item_count: usize,
+    page_size: usize,
+) -> Option<(usize, usize)> {
+    if page_number == 0 || page_number > page_count(item_count, page_size) {
+        return None;
+    }
+
+    let start = (page_number - 1) * page_size;
+    Some((start, (start + page_size).min(item_count)))
+}
+
+// The first and last pages and `window` pages on each side of the current one. Gaps of a single
+// page show that page, an ellipsis would take the same room.
+pub fn page_links(current: usize, page_count: usize, window: usize) -> Vec<PageLink> {
+    let around = current.saturating_sub(window).max(1)..=(current + window).min(page_count);
+    let mut pages: Vec<usize> = vec![1, page_count];
+    pages.extend(around);
+    pages.sort_unstable();
+    pages.dedup();
+
+    let mut links = Vec::new();
+    let mut previous = 0;
+    for page in pages {
+        match page - previous {
+            1 => {}
+            2 => links.push(PageLink::Page(page - 1)),
+            _ => links.push(PageLink::Ellipsis),
+        }
+        links.push(PageLink::Page(page));
+        previous = page;
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PageLink::{Ellipsis, Page};
+
+    #[test]
+    fn pages_cover_every_item_once() {
+        for (item_count, page_size, pages) in &[
+            (0, 5, 1),
+            (1, 5, 1),
+            (5, 5, 1),
+            (6, 5, 2),
+            (10, 5, 2),
+            (11, 5, 3),
+        ] {
+            assert_eq!(page_count(*item_count, *page_size), *pages);
+        }
+
+        for (page_number, item_count, page_size, indexes) in &[
+            (0, 10, 5, None),
+            (1, 0, 5, Some((0, 0))),
+            (2, 0, 5, None),
+            (1, 10, 5, Some((0, 5))),
+            (2, 10, 5, Some((5, 10))),
+            (3, 10, 5, None),
+            (3, 11, 5, Some((10, 11))),
+            (4, 11, 5, None),
+            (usize::MAX, 11, 5, None),
+        ] {
+            assert_eq!(
+                page_indexes(*page_number, *item_count, *page_size),
+                *indexes,
+                "page {} of {} items by {}",
+                page_number,
+                item_count,
+                page_size
+            );
+        }
+    }
+
+    #[test]
+    fn links_skip_the_pages_far_from_the_current_one() {
+        for (current, page_count, links) in &[
+            (1, 1, vec![Page(1)]),
+            (1, 3, vec![Page(1), Page(2), Page(3)]),
+            (1, 10, vec![Page(1), Page(2), Page(3), Ellipsis, Page(10)]),
+            (
+                6,
+                12,
+                vec![
+                    Page(1),
+                    Ellipsis,
+                    Page(4),
+                    Page(5),
+                    Page(6),
+                    Page(7),
+                    Page(8),
+                    Ellipsis,
+                    Page(12),
+                ],
+            ),
+            (10, 10, vec![Page(1), Ellipsis, Page(8), Page(9), Page(10)]),
+            // A gap of a single page shows it instead of an ellipsis
+            (
+                4,
+                7,
+                vec![
+                    Page(1),
+                    Page(2),
+                    Page(3),
+                    Page(4),
+                    Page(5),
+                    Page(6),
+                    Page(7),
+                ],
+            ),
+        ] {
+            assert_eq!(
+                page_links(*current, *page_count, 2),
+                *links,
+                "page {} of {}",
+                current,
+                page_count
+            );
+        }
+    }
+}
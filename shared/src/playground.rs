@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Execution {
+    pub code: String,
+}
+
+// Same fields as the playground's `/execute` response
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ExecutionOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+pub const RUST_LANGUAGES: [&str; 2] = ["rust", "rs"];
+// Most of the Rust in the articles is for the browser and can't run on the playground, so only
+// the blocks tagged with it get a run button(```rust,run)
+const RUNNABLE: &str = "run";
+
+pub fn is_rust(info: &str) -> bool {
+    let lang = info.split(',').next().unwrap_or("").trim();
+    RUST_LANGUAGES.contains(&lang)
+}
+
+pub fn is_runnable(info: &str) -> bool {
+    is_rust(info) && info.split(',').any(|tag| tag.trim() == RUNNABLE)
+}
+
+// Like rustdoc, lines starting with `# ` (or just `#`) are setup that's run but not shown, and
+// `##` escapes a line that really starts with `#`. Returns the shown code and the code to run.
+pub fn split_hidden_lines(code: &str) -> (String, String) {
+    let mut shown = String::with_capacity(code.len());
+    let mut full = String::with_capacity(code.len());
+    for line in code.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(escaped) = trimmed.strip_prefix("##") {
+            let line = format!("{}#{}", &line[..line.len() - trimmed.len()], escaped);
+            shown.push_str(&line);
+            full.push_str(&line);
+        } else if let Some(hidden) = trimmed.strip_prefix("# ") {
+            full.push_str(hidden);
+        } else if trimmed.trim_end() == "#" {
+            full.push_str(&trimmed[1..]);
+        } else {
+            shown.push_str(line);
+            full.push_str(line);
+        }
+    }
+    (shown, full)
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Revision {
+    pub id: u32,
+    // Seconds since the unix epoch
+    pub timestamp: u64,
+    pub author: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Revisions {
+    pub article: String,
+    pub revisions: Vec<Revision>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffMode {
+    #[default]
+    Unified,
+    Words,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeTag {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct DiffSegment {
+    pub tag: ChangeTag,
+    pub value: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub enum Diff {
+    Unified(String),
+    Words(Vec<DiffSegment>),
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RevisionDiff {
+    pub article: String,
+    pub from: Revision,
+    pub to: Revision,
+    pub diff: Diff,
+}
@@ -0,0 +1,23 @@
+use crate::markdown::Extensions;
+use serde::{Deserialize, Serialize};
+
+// Which of the optional parts of the blog the server has enabled
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct SiteFeatures {
+    pub comments: bool,
+    pub webmentions: bool,
+    pub playground: bool,
+}
+
+// What the client needs from the server's configuration
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SiteInfo {
+    pub title: String,
+    pub url: String,
+    pub features: SiteFeatures,
+    // What the site's markdown is rendered with
+    pub extensions: Extensions,
+    // Where the site's stylesheet and about page are, if it has its own
+    pub theme: Option<String>,
+    pub about: Option<String>,
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ViewCount {
+    // YYYY-MM-DD, in UTC
+    pub date: String,
+    pub article: String,
+    pub views: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ReferrerCount {
+    pub date: String,
+    pub article: String,
+    pub referrer: String,
+    pub views: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Stats {
+    pub since: String,
+    pub views: Vec<ViewCount>,
+    pub referrers: Vec<ReferrerCount>,
+}
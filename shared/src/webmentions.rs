@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Mention {
+    pub source: String,
+    pub target: String,
+    // Seconds since the unix epoch
+    pub verified_at: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Mentions {
+    pub article: String,
+    pub mentions: Vec<Mention>,
+}
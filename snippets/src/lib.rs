@@ -0,0 +1,139 @@
+use shared::markdown::{self, Extensions};
+use shared::playground;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Extracts the Rust snippets of the articles so they can be compiled the way rustdoc compiles
+// documentation tests: `ignore` ones are skipped, `compile_fail` ones must fail and `# ` hidden
+// lines are included. Nothing is run, so `no_run` doesn't change anything.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expectation {
+    Compiles,
+    Fails,
+}
+
+#[derive(Debug, Clone)]
+pub struct Snippet {
+    pub article: String,
+    // 1-based line of the first line of code in the article
+    pub line: usize,
+    pub code: String,
+    pub expectation: Expectation,
+}
+
+// A snippet turned into a program, with the article line of every program line(`None` for the
+// lines added around it)
+pub struct Program {
+    pub source: String,
+    pub lines: Vec<Option<usize>>,
+}
+
+fn expectation(info: &str) -> Option<Expectation> {
+    let lang = info.split_whitespace().next().unwrap_or("");
+    if !playground::is_rust(lang) {
+        return None;
+    }
+
+    let flags: Vec<&str> = lang.split(',').map(str::trim).collect();
+    if flags.contains(&"ignore") {
+        None
+    } else if flags.contains(&"compile_fail") {
+        Some(Expectation::Fails)
+    } else {
+        Some(Expectation::Compiles)
+    }
+}
+
+// Snippets may start with the file they belong to, a path and a `---` line, which isn't code
+fn strip_file_header(snippet: Snippet) -> Snippet {
+    let mut lines = snippet.code.splitn(3, '\n');
+    match (lines.next(), lines.next(), lines.next()) {
+        (Some(path), Some(delimiter), Some(code))
+            if delimiter.trim() == "---"
+                && path.contains('.')
+                && !path.trim().contains(char::is_whitespace) =>
+        {
+            Snippet {
+                code: code.to_string(),
+                line: snippet.line + 2,
+                ..snippet
+            }
+        }
+        _ => snippet,
+    }
+}
+
+pub fn extract(article: &str, content: &str) -> Vec<Snippet> {
+    markdown::code_blocks(content, Extensions::default())
+        .into_iter()
+        .filter_map(|block| {
+            let expectation = expectation(block.info.as_deref()?)?;
+            Some(strip_file_header(Snippet {
+                article: article.to_string(),
+                line: content[..block.code_start].matches('\n').count() + 1,
+                code: block.code,
+                expectation,
+            }))
+        })
+        .collect()
+}
+
+pub fn extract_dir(dir: &Path) -> io::Result<Vec<Snippet>> {
+    let mut articles: Vec<_> = fs::read_dir(dir)?
+        .collect::<io::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|entry| entry.path().extension() == Some("md".as_ref()))
+        .collect();
+    articles.sort_by_key(|entry| entry.file_name());
+
+    let mut snippets = Vec::new();
+    for entry in articles {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        snippets.extend(extract(&name, &fs::read_to_string(entry.path())?));
+    }
+    Ok(snippets)
+}
+
+impl Snippet {
+    // Like rustdoc, snippets without a `main` are wrapped in one, with crate attributes and
+    // `extern crate`s moved out of it
+    pub fn program(&self) -> Program {
+        let (_, code) = playground::split_hidden_lines(&self.code);
+        let mut program = Program {
+            source: String::from("#![allow(unused)]\n"),
+            lines: vec![None],
+        };
+        let mut push = |line: &str, article_line: Option<usize>| {
+            program.source.push_str(line);
+            program.source.push('\n');
+            program.lines.push(article_line);
+        };
+
+        let lines: Vec<(usize, &str)> = code
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (self.line + i, line))
+            .collect();
+        if code.contains("fn main") {
+            for (article_line, line) in lines {
+                push(line, Some(article_line));
+            }
+        } else {
+            let is_crate_level = |line: &str| {
+                let line = line.trim_start();
+                line.starts_with("#![") || line.starts_with("extern crate")
+            };
+            for (article_line, line) in lines.iter().filter(|(_, l)| is_crate_level(l)) {
+                push(line, Some(*article_line));
+            }
+            push("fn main() {", None);
+            for (article_line, line) in lines.iter().filter(|(_, l)| !is_crate_level(l)) {
+                push(line, Some(*article_line));
+            }
+            push("}", None);
+        }
+        program
+    }
+}
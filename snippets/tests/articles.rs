@@ -0,0 +1,116 @@
+use snippets::{Expectation, Program, Snippet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const ARTICLES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../client/static/articles");
+const DEPENDENCIES: &str = include_str!("../dependencies.toml");
+
+// Every snippet is a binary of a scratch package depending on the pinned crates. It's kept in the
+// target directory so they're only built once.
+fn scratch_package(snippets: &[Snippet]) -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/snippets");
+    let bin_dir = dir.join("src/bin");
+    // Left over binaries from previous runs would be checked too
+    let _ = fs::remove_dir_all(&bin_dir);
+    fs::create_dir_all(&bin_dir).unwrap();
+
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"article-snippets\"\nversion = \"0.1.0\"\nedition = \"2018\"\n\
+            \n# Not part of any workspace\n[workspace]\n\n{}",
+            DEPENDENCIES
+        ),
+    )
+    .unwrap();
+    for (i, snippet) in snippets.iter().enumerate() {
+        fs::write(
+            bin_dir.join(format!("snippet_{}.rs", i)),
+            snippet.program().source,
+        )
+        .unwrap();
+    }
+    dir
+}
+
+// `cargo check` errors for the snippet, with their lines pointing to the article
+fn check(package: &Path, i: usize, snippet: &Snippet, program: &Program) -> Vec<String> {
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--quiet", "--message-format", "short", "--bin"])
+        .arg(format!("snippet_{}", i))
+        .current_dir(package)
+        .output()
+        .expect("Couldn't run cargo");
+    if output.status.success() {
+        return Vec::new();
+    }
+
+    let file = format!("src/bin/snippet_{}.rs:", i);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let errors: Vec<String> = stderr
+        .lines()
+        .filter_map(|line| {
+            // src/bin/snippet_0.rs:12:5: error[E0425]: cannot find value `x` in this scope
+            let location = line.strip_prefix(&file)?;
+            let mut parts = location.splitn(3, ':');
+            let line: usize = parts.next()?.parse().ok()?;
+            let message = parts.nth(1)?.trim();
+            if !message.starts_with("error") {
+                return None;
+            }
+
+            let article_line = program.lines.get(line - 1).copied().flatten();
+            Some(format!(
+                "{}:{}: {}",
+                snippet.article,
+                article_line.unwrap_or(snippet.line),
+                message
+            ))
+        })
+        .collect();
+
+    // Failures unrelated to the snippet itself(e.g. resolving dependencies) are reported as is
+    if errors.is_empty() {
+        vec![format!(
+            "{}:{}: {}",
+            snippet.article,
+            snippet.line,
+            stderr.trim()
+        )]
+    } else {
+        errors
+    }
+}
+
+#[test]
+fn article_snippets_compile() {
+    let snippets = snippets::extract_dir(Path::new(ARTICLES_DIR)).unwrap();
+    let package = scratch_package(&snippets);
+
+    // Everything being `ignore` would pass without checking anything
+    let compiled = snippets
+        .iter()
+        .filter(|snippet| snippet.expectation == Expectation::Compiles)
+        .count();
+    assert!(compiled > 0, "No article snippet is compiled");
+
+    let mut failures = Vec::new();
+    for (i, snippet) in snippets.iter().enumerate() {
+        let errors = check(&package, i, snippet, &snippet.program());
+        match snippet.expectation {
+            Expectation::Compiles => failures.extend(errors),
+            Expectation::Fails if errors.is_empty() => failures.push(format!(
+                "{}:{}: compiled but is marked `compile_fail`",
+                snippet.article, snippet.line
+            )),
+            Expectation::Fails => {}
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "Article snippets failed:\n{}",
+        failures.join("\n")
+    );
+}